@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use itertools::Itertools;
+use stwo_verifier_no_std::backend::cpu::quotients::{accumulate_row_quotients, quotient_constants};
+use stwo_verifier_no_std::channel::{Blake2sChannel, Channel};
+use stwo_verifier_no_std::circle::CirclePoint;
+use stwo_verifier_no_std::fields::m31::BaseField;
+use stwo_verifier_no_std::fields::qm31::SecureField;
+use stwo_verifier_no_std::pcs::quotients::{fri_answers, ColumnSampleBatch, PointSample};
+use stwo_verifier_no_std::pcs::TreeVec;
+use stwo_verifier_no_std::poly::circle::CanonicCoset;
+use stwo_verifier_no_std::utils::bit_reverse_index;
+
+const N_QUERIES: usize = 70;
+
+/// A wide AIR: many columns of the same size, plus an interaction tree of smaller columns.
+const TREES: [(u32, usize); 2] = [(16, 300), (14, 40)];
+
+fn bench_fri_answers(c: &mut Criterion) {
+    let channel = &mut Blake2sChannel::default();
+    let random_coeff = channel.draw_felt();
+    let points = (0..2)
+        .map(|_| CirclePoint::<SecureField>::get_random_point(channel))
+        .collect_vec();
+
+    let column_log_sizes = TreeVec::new(
+        TREES
+            .iter()
+            .map(|&(log_size, n_columns)| vec![log_size; n_columns])
+            .collect_vec(),
+    );
+    let n_columns_per_log_size = TREES
+        .iter()
+        .map(|&(log_size, n_columns)| BTreeMap::from([(log_size, n_columns)]))
+        .collect_vec();
+    let query_positions_per_log_size: BTreeMap<u32, Vec<usize>> = TREES
+        .iter()
+        .map(|&(log_size, _)| {
+            let positions = (0..N_QUERIES)
+                .map(|i| (i * 7919) % (1 << log_size))
+                .sorted()
+                .dedup()
+                .collect();
+            (log_size, positions)
+        })
+        .collect();
+    let queried_values = TreeVec::new(
+        TREES
+            .iter()
+            .map(|&(log_size, n_columns)| {
+                let n_queries = query_positions_per_log_size[&log_size].len();
+                (0..n_queries * n_columns)
+                    .map(|i| BaseField::from_u32_unchecked(i as u32))
+                    .collect_vec()
+            })
+            .collect_vec(),
+    );
+
+    let samples = || {
+        column_log_sizes.as_ref().map(|columns| {
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, _)| {
+                    // Every other column is also sampled at a second point, as with masks of
+                    // consecutive rows.
+                    points[..1 + i % 2]
+                        .iter()
+                        .map(|&point| PointSample {
+                            point,
+                            value: SecureField::from_u32_unchecked(i as u32, 1, 2, 3),
+                        })
+                        .collect_vec()
+                })
+                .collect_vec()
+        })
+    };
+
+    assert_eq!(
+        fri_answers(
+            column_log_sizes.clone(),
+            samples(),
+            random_coeff,
+            &query_positions_per_log_size,
+            queried_values.clone(),
+            TreeVec::new(n_columns_per_log_size.iter().collect()),
+        )
+        .unwrap(),
+        fri_answers_per_query(
+            &column_log_sizes,
+            &samples(),
+            random_coeff,
+            &query_positions_per_log_size,
+            &queried_values,
+            &n_columns_per_log_size,
+        )
+    );
+
+    let mut group = c.benchmark_group("fri_answers");
+    group.bench_function("batch_inversion", |b| {
+        b.iter_batched(
+            || (samples(), queried_values.clone()),
+            |(samples, queried_values)| {
+                fri_answers(
+                    column_log_sizes.clone(),
+                    samples,
+                    random_coeff,
+                    &query_positions_per_log_size,
+                    queried_values,
+                    TreeVec::new(n_columns_per_log_size.iter().collect()),
+                )
+                .unwrap()
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    // The implementation before batch inversion, which inverts the denominators of each query
+    // separately.
+    let per_query_samples = samples();
+    group.bench_function("per_query_inversion", |b| {
+        b.iter(|| {
+            fri_answers_per_query(
+                &column_log_sizes,
+                &per_query_samples,
+                random_coeff,
+                &query_positions_per_log_size,
+                &queried_values,
+                &n_columns_per_log_size,
+            )
+        });
+    });
+    group.finish();
+}
+
+/// Answers the queries of each log size in descending order, one query at a time.
+fn fri_answers_per_query(
+    column_log_sizes: &TreeVec<Vec<u32>>,
+    samples: &TreeVec<Vec<Vec<PointSample>>>,
+    random_coeff: SecureField,
+    query_positions_per_log_size: &BTreeMap<u32, Vec<usize>>,
+    queried_values: &TreeVec<Vec<BaseField>>,
+    n_columns_per_log_size: &[BTreeMap<u32, usize>],
+) -> Vec<Vec<SecureField>> {
+    let mut queried_values = queried_values
+        .iter()
+        .map(|values| values.iter().copied())
+        .collect_vec();
+    query_positions_per_log_size
+        .iter()
+        .rev()
+        .map(|(&log_size, query_positions)| {
+            let samples = column_log_sizes
+                .iter()
+                .flatten()
+                .zip(samples.iter().flatten())
+                .filter(|(&column_log_size, _)| column_log_size == log_size)
+                .map(|(_, samples)| samples)
+                .collect_vec();
+            let sample_batches = ColumnSampleBatch::new_vec(&samples);
+            let quotient_constants = quotient_constants(&sample_batches, random_coeff);
+            let commitment_domain = CanonicCoset::new(log_size).circle_domain();
+            query_positions
+                .iter()
+                .map(|&position| {
+                    let domain_point = commitment_domain.at(bit_reverse_index(position, log_size));
+                    let queried_values_at_row = queried_values
+                        .iter_mut()
+                        .zip(n_columns_per_log_size)
+                        .flat_map(|(values, n_columns)| {
+                            values
+                                .by_ref()
+                                .take(*n_columns.get(&log_size).unwrap_or(&0))
+                                .collect_vec()
+                        })
+                        .collect_vec();
+                    accumulate_row_quotients(
+                        &sample_batches,
+                        &queried_values_at_row,
+                        &quotient_constants,
+                        domain_point,
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = bench_fri_answers);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use itertools::Itertools;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use stwo_verifier_no_std::backend::CpuBackend;
+use stwo_verifier_no_std::fields::m31::BaseField;
+use stwo_verifier_no_std::vcs::blake2_hash::Blake2sHash;
+use stwo_verifier_no_std::vcs::blake2_merkle::Blake2sMerkleHasher;
+use stwo_verifier_no_std::vcs::blake2s::compress;
+use stwo_verifier_no_std::vcs::blake2s_ref;
+use stwo_verifier_no_std::vcs::ops::MerkleHasher;
+use stwo_verifier_no_std::vcs::prover::MerkleProver;
+use stwo_verifier_no_std::vcs::verifier::MerkleVerifier;
+
+const N_QUERIES: usize = 70;
+
+/// A commitment tree as in a wide AIR: many columns of the largest size, and a few smaller ones.
+const COLUMNS: [(u32, usize); 2] = [(16, 200), (14, 20)];
+
+fn bench_compress(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (h_vecs, msg_vecs): ([u32; 8], [u32; 16]) = (rng.gen(), rng.gen());
+
+    let mut group = c.benchmark_group("blake2s_compress");
+    group.throughput(Throughput::Bytes(64));
+    group.bench_function("reference", |b| {
+        b.iter(|| blake2s_ref::compress(black_box(h_vecs), black_box(msg_vecs), 0, 0, 0, 0))
+    });
+    group.bench_function("optimized", |b| {
+        b.iter(|| compress(black_box(h_vecs), black_box(msg_vecs), 0, 0, 0, 0))
+    });
+    group.finish();
+}
+
+fn bench_hash_node(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let children_hashes = Some((Blake2sHash(rng.gen()), Blake2sHash(rng.gen())));
+
+    let mut group = c.benchmark_group("hash_node");
+    for n_columns in [0, 16, 200] {
+        let values = (0..n_columns)
+            .map(|_| BaseField::from(rng.gen_range(0..1 << 30)))
+            .collect_vec();
+        group.bench_function(format!("{n_columns} columns"), |b| {
+            b.iter(|| Blake2sMerkleHasher::hash_node(black_box(children_hashes), &values))
+        });
+    }
+    group.finish();
+}
+
+fn bench_merkle_verify(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (column_log_sizes, columns): (Vec<u32>, Vec<Vec<BaseField>>) = COLUMNS
+        .iter()
+        .flat_map(|&(log_size, n_columns)| vec![log_size; n_columns])
+        .map(|log_size| {
+            let column = (0..1 << log_size)
+                .map(|_| BaseField::from(rng.gen_range(0..1 << 30)))
+                .collect();
+            (log_size, column)
+        })
+        .unzip();
+    let queries: BTreeMap<u32, Vec<usize>> = COLUMNS
+        .iter()
+        .map(|&(log_size, _)| {
+            let positions = (0..N_QUERIES)
+                .map(|_| rng.gen_range(0..1 << log_size))
+                .sorted()
+                .dedup()
+                .collect();
+            (log_size, positions)
+        })
+        .collect();
+    let prover =
+        MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(columns.iter().collect_vec());
+    let (queried_values, decommitment) = prover.decommit(&queries, columns.iter().collect_vec());
+    let verifier = MerkleVerifier::new(prover.root(), column_log_sizes);
+
+    c.bench_function("merkle_verify", |b| {
+        b.iter_batched(
+            || (queried_values.clone(), decommitment.clone()),
+            |(queried_values, decommitment)| {
+                verifier
+                    .verify(&queries, queried_values, decommitment)
+                    .unwrap()
+            },
+            BatchSize::LargeInput,
+        );
+    });
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = bench_compress, bench_hash_node, bench_merkle_verify);
+criterion_main!(benches);
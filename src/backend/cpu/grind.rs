@@ -0,0 +1,69 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::CpuBackend;
+use crate::channel::Blake2sChannel;
+use crate::proof_of_work::{verify_pow, GrindOps};
+
+/// Number of nonces each round of the multi-threaded search checks.
+#[cfg(feature = "parallel")]
+const NONCES_PER_ROUND: u64 = 1 << 12;
+
+impl GrindOps<Blake2sChannel> for CpuBackend {
+    /// Returns the smallest valid nonce.
+    ///
+    /// With the `parallel` feature, nonces are checked in rounds on all threads. The result is the
+    /// same as the single-threaded search.
+    fn grind(channel: &Blake2sChannel, pow_bits: u32) -> u64 {
+        #[cfg(not(feature = "parallel"))]
+        {
+            (0..)
+                .find(|&nonce| verify_pow(&mut channel.clone(), nonce, pow_bits))
+                .unwrap()
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            (0..)
+                .step_by(NONCES_PER_ROUND as usize)
+                .find_map(|round_start| {
+                    (round_start..round_start + NONCES_PER_ROUND)
+                        .into_par_iter()
+                        .find_first(|&nonce| verify_pow(&mut channel.clone(), nonce, pow_bits))
+                })
+                .unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::CpuBackend;
+    use crate::channel::{Blake2sChannel, Channel};
+    use crate::proof_of_work::{verify_pow, GrindOps};
+
+    #[test]
+    fn test_grind_finds_smallest_nonce() {
+        let mut channel = Blake2sChannel::default();
+        channel.mix_u64(42);
+        let pow_bits = 10;
+
+        let nonce = CpuBackend::grind(&channel, pow_bits);
+
+        assert!(verify_pow(&mut channel.clone(), nonce, pow_bits));
+        assert!((0..nonce).all(|nonce| !verify_pow(&mut channel.clone(), nonce, pow_bits)));
+    }
+
+    #[test]
+    fn test_verify_pow_mixes_nonce() {
+        let channel = Blake2sChannel::default();
+        let nonce = CpuBackend::grind(&channel, 4);
+        let mut verifier_channel = channel.clone();
+        let mut expected_channel = channel.clone();
+
+        verify_pow(&mut verifier_channel, nonce, 4);
+        expected_channel.mix_u64(nonce);
+
+        assert_eq!(verifier_channel.digest(), expected_channel.digest());
+    }
+}
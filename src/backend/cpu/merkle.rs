@@ -0,0 +1,25 @@
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+
+use super::CpuBackend;
+use crate::backend::Col;
+use crate::fields::m31::BaseField;
+use crate::vcs::ops::{MerkleHasher, MerkleOps};
+
+impl<H: MerkleHasher> MerkleOps<H> for CpuBackend {
+    fn commit_on_layer(
+        log_size: u32,
+        prev_layer: Option<&Vec<H::Hash>>,
+        columns: &[&Col<Self, BaseField>],
+    ) -> Vec<H::Hash> {
+        (0..1 << log_size)
+            .map(|i| {
+                H::hash_node(
+                    prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
+                    &columns.iter().map(|column| column[i]).collect_vec(),
+                )
+            })
+            .collect()
+    }
+}
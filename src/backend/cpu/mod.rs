@@ -2,7 +2,8 @@ pub mod accumulation;
 // mod blake2s;
 pub mod circle;
 // mod fri;
-// mod grind;
+mod grind;
+mod merkle;
 // pub mod lookups;
 // #[cfg(not(target_arch = "wasm32"))]
 // mod poseidon252;
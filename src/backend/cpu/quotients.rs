@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use itertools::{izip, zip_eq};
+use itertools::{izip, zip_eq, Itertools};
 use num_traits::{One, Zero};
 
 // use super::CpuBackend;
@@ -120,22 +120,24 @@ fn denominator_inverses(
     sample_batches: &[ColumnSampleBatch],
     domain_point: CirclePoint<M31>,
 ) -> Vec<CM31> {
-    let mut denominators = Vec::new();
-
-    // We want a P to be on a line that passes through a point Pr + uPi in QM31^2, and its conjugate
-    // Pr - uPi. Thus, Pr - P is parallel to Pi. Or, (Pr - P).x * Pi.y - (Pr - P).y * Pi.x = 0.
-    for sample_batch in sample_batches {
-        // Extract Pr, Pi.
-        let prx = sample_batch.point.x.0;
-        let pry = sample_batch.point.y.0;
-        let pix = sample_batch.point.x.1;
-        let piy = sample_batch.point.y.1;
-        denominators.push((prx - domain_point.x) * piy - (pry - domain_point.y) * pix);
-    }
+    let denominators = sample_batches
+        .iter()
+        .map(|sample_batch| denominator(sample_batch.point, domain_point))
+        .collect_vec();
 
     CM31::batch_inverse(&denominators)
 }
 
+/// Returns the denominator of the quotient by the line through `sample_point` and its conjugate,
+/// evaluated at `domain_point`.
+pub fn denominator(sample_point: CirclePoint<SecureField>, domain_point: CirclePoint<M31>) -> CM31 {
+    let prx = sample_point.x.0;
+    let pry = sample_point.y.0;
+    let pix = sample_point.x.1;
+    let piy = sample_point.y.1;
+    (prx - domain_point.x) * piy - (pry - domain_point.y) * pix
+}
+
 pub fn quotient_constants(
     sample_batches: &[ColumnSampleBatch],
     random_coeff: SecureField,
@@ -190,3 +192,70 @@ pub struct QuotientConstants {
 //         assert!(quot_poly_base_field.is_in_fri_space(LOG_SIZE));
 //     }
 // }
+
+/// [QuotientConstants] rearranged for evaluating the quotients of many rows.
+///
+/// The linear terms `a * y + b` of all the columns in a batch only depend on the row through `y`,
+/// so their coefficients are summed up once per batch instead of once per column and row.
+pub struct RowQuotientConstants {
+    /// For each sample batch, the sampled column indices and the coefficient of their value.
+    pub column_coeffs: Vec<Vec<(usize, SecureField)>>,
+    /// For each sample batch, the sums of the `a` and `b` line coefficients of its columns.
+    pub line_coeff_sums: Vec<(SecureField, SecureField)>,
+    pub batch_random_coeffs: Vec<SecureField>,
+}
+
+impl RowQuotientConstants {
+    pub fn new(sample_batches: &[ColumnSampleBatch], random_coeff: SecureField) -> Self {
+        let QuotientConstants {
+            line_coeffs,
+            batch_random_coeffs,
+        } = quotient_constants(sample_batches, random_coeff);
+
+        let (column_coeffs, line_coeff_sums) = zip_eq(sample_batches, line_coeffs)
+            .map(|(sample_batch, line_coeffs)| {
+                let mut a_sum = SecureField::zero();
+                let mut b_sum = SecureField::zero();
+                let column_coeffs = zip_eq(&sample_batch.columns_and_values, line_coeffs)
+                    .map(|(&(column_index, _), (a, b, c))| {
+                        a_sum += a;
+                        b_sum += b;
+                        (column_index, c)
+                    })
+                    .collect();
+                (column_coeffs, (a_sum, b_sum))
+            })
+            .unzip();
+
+        Self {
+            column_coeffs,
+            line_coeff_sums,
+            batch_random_coeffs,
+        }
+    }
+}
+
+/// Same as [accumulate_row_quotients], with the denominator inverses of each sample batch at the
+/// row's domain point precomputed (see [denominator]).
+pub fn accumulate_row_quotients_with_inverses(
+    quotient_constants: &RowQuotientConstants,
+    queried_values_at_row: &[BaseField],
+    domain_point_y: BaseField,
+    denominator_inverses: &[CM31],
+) -> SecureField {
+    let mut row_accumulator = SecureField::zero();
+    for (column_coeffs, &(a_sum, b_sum), batch_coeff, denominator_inverse) in izip!(
+        &quotient_constants.column_coeffs,
+        &quotient_constants.line_coeff_sums,
+        &quotient_constants.batch_random_coeffs,
+        denominator_inverses
+    ) {
+        let mut numerator = -(a_sum * domain_point_y + b_sum);
+        for &(column_index, c) in column_coeffs {
+            numerator += c * queried_values_at_row[column_index];
+        }
+
+        row_accumulator = row_accumulator * *batch_coeff + numerator.mul_cm31(*denominator_inverse);
+    }
+    row_accumulator
+}
@@ -8,7 +8,7 @@ use crate::fields::qm31::SecureField;
 use crate::fields::secure_column::SECURE_EXTENSION_DEGREE;
 use crate::fields::IntoSlice;
 use crate::vcs::blake2_hash::{Blake2sHash, Blake2sHasher};
-use crate::vcs::blake2s_ref::compress;
+use crate::vcs::blake2s::compress;
 
 pub const BLAKE_BYTES_PER_HASH: usize = 32;
 pub const FELTS_PER_HASH: usize = 8;
@@ -21,6 +21,16 @@ pub struct Blake2sChannel {
 }
 
 impl Blake2sChannel {
+    /// Creates a channel whose initial digest is the hash of `domain_separator`.
+    ///
+    /// Proofs of different protocols, or of different versions of a protocol, then draw unrelated
+    /// randomness even if their messages coincide.
+    pub fn with_domain_separator(domain_separator: &[u8]) -> Self {
+        Self {
+            digest: Blake2sHasher::hash(domain_separator),
+            channel_time: ChannelTime::default(),
+        }
+    }
     pub const fn digest(&self) -> Blake2sHash {
         self.digest
     }
@@ -80,6 +90,22 @@ impl Channel for Blake2sChannel {
         self.update_digest(unsafe { core::mem::transmute::<[u32; 8], Blake2sHash>(res) });
     }
 
+    fn mix_u32s(&mut self, data: &[u32]) {
+        let bytes = data
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        self.mix_bytes(&bytes);
+    }
+
+    fn mix_bytes(&mut self, data: &[u8]) {
+        let mut hasher = Blake2sHasher::new();
+        hasher.update(self.digest.as_ref());
+        hasher.update(data);
+
+        self.update_digest(hasher.finalize());
+    }
+
     fn draw_felt(&mut self) -> SecureField {
         let felts: [BaseField; FELTS_PER_HASH] = self.draw_base_felts();
         SecureField::from_m31_array(felts[..SECURE_EXTENSION_DEGREE].try_into().unwrap())
@@ -111,77 +137,136 @@ impl Channel for Blake2sChannel {
         self.channel_time.inc_sent();
         Blake2sHasher::hash(&hash_input).into()
     }
+
+    fn draw_u32s(&mut self) -> Vec<u32> {
+        self.draw_random_bytes()
+            .chunks_exact(N_BYTES_FELT)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use core::collections::BTreeSet;
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeSet;
+    use alloc::vec::Vec;
 
-//     use crate::core::channel::blake2s::Blake2sChannel;
-//     use crate::core::channel::Channel;
-//     use crate::core::fields::qm31::SecureField;
-//     use crate::m31;
+    use crate::channel::blake2s::Blake2sChannel;
+    use crate::channel::Channel;
+    use crate::fields::qm31::SecureField;
+    use crate::m31;
+    use crate::vcs::blake2_hash::Blake2sHasher;
 
-//     #[test]
-//     fn test_channel_time() {
-//         let mut channel = Blake2sChannel::default();
+    #[test]
+    fn test_channel_time() {
+        let mut channel = Blake2sChannel::default();
 
-//         assert_eq!(channel.channel_time.n_challenges, 0);
-//         assert_eq!(channel.channel_time.n_sent, 0);
+        assert_eq!(channel.channel_time.n_challenges, 0);
+        assert_eq!(channel.channel_time.n_sent, 0);
 
-//         channel.draw_random_bytes();
-//         assert_eq!(channel.channel_time.n_challenges, 0);
-//         assert_eq!(channel.channel_time.n_sent, 1);
+        channel.draw_random_bytes();
+        assert_eq!(channel.channel_time.n_challenges, 0);
+        assert_eq!(channel.channel_time.n_sent, 1);
 
-//         channel.draw_felts(9);
-//         assert_eq!(channel.channel_time.n_challenges, 0);
-//         assert_eq!(channel.channel_time.n_sent, 6);
-//     }
+        channel.draw_felts(9);
+        assert_eq!(channel.channel_time.n_challenges, 0);
+        assert_eq!(channel.channel_time.n_sent, 6);
+    }
 
-//     #[test]
-//     fn test_draw_random_bytes() {
-//         let mut channel = Blake2sChannel::default();
+    #[test]
+    fn test_draw_random_bytes() {
+        let mut channel = Blake2sChannel::default();
 
-//         let first_random_bytes = channel.draw_random_bytes();
+        let first_random_bytes = channel.draw_random_bytes();
 
-//         // Assert that next random bytes are different.
-//         assert_ne!(first_random_bytes, channel.draw_random_bytes());
-//     }
+        // Assert that next random bytes are different.
+        assert_ne!(first_random_bytes, channel.draw_random_bytes());
+    }
 
-//     #[test]
-//     pub fn test_draw_felt() {
-//         let mut channel = Blake2sChannel::default();
+    #[test]
+    pub fn test_draw_felt() {
+        let mut channel = Blake2sChannel::default();
 
-//         let first_random_felt = channel.draw_felt();
+        let first_random_felt = channel.draw_felt();
 
-//         // Assert that next random felt is different.
-//         assert_ne!(first_random_felt, channel.draw_felt());
-//     }
+        // Assert that next random felt is different.
+        assert_ne!(first_random_felt, channel.draw_felt());
+    }
 
-//     #[test]
-//     pub fn test_draw_felts() {
-//         let mut channel = Blake2sChannel::default();
+    #[test]
+    pub fn test_draw_felts() {
+        let mut channel = Blake2sChannel::default();
 
-//         let mut random_felts = channel.draw_felts(5);
-//         random_felts.extend(channel.draw_felts(4));
+        let mut random_felts = channel.draw_felts(5);
+        random_felts.extend(channel.draw_felts(4));
 
-//         // Assert that all the random felts are unique.
-//         assert_eq!(
-//             random_felts.len(),
-//             random_felts.iter().collect::<BTreeSet<_>>().len()
-//         );
-//     }
+        // Assert that all the random felts are unique.
+        assert_eq!(
+            random_felts.len(),
+            random_felts.iter().collect::<BTreeSet<_>>().len()
+        );
+    }
 
-//     #[test]
-//     pub fn test_mix_felts() {
-//         let mut channel = Blake2sChannel::default();
-//         let initial_digest = channel.digest;
-//         let felts: Vec<SecureField> = (0..2)
-//             .map(|i| SecureField::from(m31!(i + 1923782)))
-//             .collect();
+    #[test]
+    pub fn test_mix_felts() {
+        let mut channel = Blake2sChannel::default();
+        let initial_digest = channel.digest;
+        let felts: Vec<SecureField> = (0..2)
+            .map(|i| SecureField::from(m31!(i + 1923782)))
+            .collect();
 
-//         channel.mix_felts(felts.as_slice());
+        channel.mix_felts(felts.as_slice());
 
-//         assert_ne!(initial_digest, channel.digest);
-//     }
-// }
+        assert_ne!(initial_digest, channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_u32s() {
+        let mut channel = Blake2sChannel::default();
+        let mut felts_channel = Blake2sChannel::default();
+
+        channel.mix_u32s(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        felts_channel.mix_felts(&[
+            SecureField::from_u32_unchecked(1, 2, 3, 4),
+            SecureField::from_u32_unchecked(5, 6, 7, 8),
+        ]);
+
+        // M31 elements are mixed as their little-endian u32 representation.
+        assert_eq!(channel.digest, felts_channel.digest);
+        assert_eq!(channel.channel_time.n_challenges, 1);
+    }
+
+    #[test]
+    pub fn test_mix_bytes() {
+        let mut channel = Blake2sChannel::default();
+        let initial_digest = channel.digest;
+
+        channel.mix_bytes(b"program hash");
+
+        let expected_digest =
+            Blake2sHasher::hash(&[initial_digest.as_ref(), b"program hash"].concat());
+        assert_eq!(channel.digest, expected_digest);
+    }
+
+    #[test]
+    pub fn test_draw_u32s() {
+        let mut channel = Blake2sChannel::default();
+        let random_bytes = channel.clone().draw_random_bytes();
+
+        let random_u32s = channel.draw_u32s();
+
+        assert_eq!(random_u32s.len(), 8);
+        assert_eq!(random_u32s[0].to_le_bytes(), random_bytes[..4]);
+        assert_ne!(random_u32s, channel.draw_u32s());
+    }
+
+    #[test]
+    pub fn test_domain_separator() {
+        let mut channel = Blake2sChannel::with_domain_separator(b"protocol-v1");
+        let mut other_channel = Blake2sChannel::with_domain_separator(b"protocol-v2");
+
+        assert_eq!(channel.digest, Blake2sHasher::hash(b"protocol-v1"));
+        assert_eq!(channel.channel_time.n_challenges, 0);
+        assert_ne!(channel.draw_felt(), other_channel.draw_felt());
+    }
+}
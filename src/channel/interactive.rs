@@ -0,0 +1,737 @@
+//! A challenge source for interactive verification.
+//!
+//! [InteractiveChannel] forwards every prover message the verifier absorbs to a [MessageQueue],
+//! and takes every challenge from it. The queue can be backed by an interactive prover, an on-chain
+//! contract or a public randomness beacon. Challenges must only be released after the messages
+//! that precede them were received, since nothing binds them to the transcript otherwise.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use thiserror_no_std::Error;
+
+use super::{ChallengeSource, MerkleChannel};
+use crate::fields::qm31::SecureField;
+use crate::queries::UPPER_BOUND_QUERY_BYTES;
+use crate::vcs::ops::MerkleHasher;
+
+/// A prover message absorbed by the verifier.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProverMessage {
+    Felts(Vec<SecureField>),
+    U64(u64),
+    /// A Merkle root, formatted with its [Display](core::fmt::Display) implementation.
+    Root(String),
+}
+
+/// The external side of an [InteractiveChannel].
+pub trait MessageQueue {
+    /// Delivers a prover message absorbed by the verifier.
+    fn send(&mut self, message: ProverMessage);
+    /// Returns the next `n_felts` field element challenges.
+    fn receive_felts(&mut self, n_felts: usize) -> Vec<SecureField>;
+    /// Returns the next random bytes challenge. Query positions are sampled from 4 byte chunks.
+    fn receive_random_bytes(&mut self) -> Vec<u8>;
+}
+
+/// A challenge from a [MessageQueue] that doesn't have the shape the verifier drew.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum ChallengeError {
+    #[error("received {actual} field elements for a draw of {expected}")]
+    WrongFeltCount { expected: usize, actual: usize },
+    #[error(
+        "received {actual} random bytes, which is not a positive multiple of {}",
+        UPPER_BOUND_QUERY_BYTES
+    )]
+    MalformedRandomBytes { actual: usize },
+}
+
+impl ChallengeError {
+    /// A stable numeric code identifying the variant. Codes are never reused or reassigned.
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::WrongFeltCount { .. } => 400,
+            Self::MalformedRandomBytes { .. } => 401,
+        }
+    }
+}
+
+/// A [ChallengeSource] that takes its challenges from a [MessageQueue].
+///
+/// There is no proof of work in interactive verification: [ChallengeSource::trailing_zeros] is
+/// always 0, so the [PcsConfig](crate::pcs::PcsConfig) must have `pow_bits` set to 0 and no
+/// grinding points.
+///
+/// The queue is not trusted to answer with challenges of the right shape. A malformed challenge is
+/// replaced by zeroes and recorded, see [ChallengeSource::malformed_challenge], and verification
+/// then fails with [VerificationError::Challenge](crate::VerificationError::Challenge).
+#[derive(Clone, Debug, Default)]
+pub struct InteractiveChannel<Q: MessageQueue> {
+    pub queue: Q,
+    malformed_challenge: Option<ChallengeError>,
+}
+
+impl<Q: MessageQueue> InteractiveChannel<Q> {
+    pub const fn new(queue: Q) -> Self {
+        Self {
+            queue,
+            malformed_challenge: None,
+        }
+    }
+
+    /// Records `error`, unless an earlier challenge was already malformed.
+    fn reject(&mut self, error: ChallengeError) {
+        self.malformed_challenge.get_or_insert(error);
+    }
+}
+
+impl<Q: MessageQueue> ChallengeSource for InteractiveChannel<Q> {
+    fn trailing_zeros(&self) -> u32 {
+        0
+    }
+
+    fn mix_felts(&mut self, felts: &[SecureField]) {
+        self.queue.send(ProverMessage::Felts(felts.to_vec()));
+    }
+
+    fn mix_u64(&mut self, value: u64) {
+        self.queue.send(ProverMessage::U64(value));
+    }
+
+    fn draw_felt(&mut self) -> SecureField {
+        self.draw_felts(1)[0]
+    }
+
+    fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+        let felts = self.queue.receive_felts(n_felts);
+        if felts.len() != n_felts {
+            self.reject(ChallengeError::WrongFeltCount {
+                expected: n_felts,
+                actual: felts.len(),
+            });
+            return vec![SecureField::zero(); n_felts];
+        }
+        felts
+    }
+
+    fn draw_random_bytes(&mut self) -> Vec<u8> {
+        let bytes = self.queue.receive_random_bytes();
+        let chunks = bytes.chunks_exact(UPPER_BOUND_QUERY_BYTES);
+        if chunks.len() == 0 || !chunks.remainder().is_empty() {
+            self.reject(ChallengeError::MalformedRandomBytes {
+                actual: bytes.len(),
+            });
+            // Query sampling needs at least one chunk to terminate.
+            return vec![0; UPPER_BOUND_QUERY_BYTES];
+        }
+        bytes
+    }
+
+    fn malformed_challenge(&self) -> Option<ChallengeError> {
+        self.malformed_challenge
+    }
+}
+
+/// A [MerkleChannel] that drives verification with an [InteractiveChannel].
+pub struct InteractiveMerkleChannel<H: MerkleHasher, Q: MessageQueue>(PhantomData<(H, Q)>);
+
+impl<H: MerkleHasher, Q: MessageQueue> Default for InteractiveMerkleChannel<H, Q> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<H: MerkleHasher, Q: MessageQueue> MerkleChannel for InteractiveMerkleChannel<H, Q> {
+    type C = InteractiveChannel<Q>;
+    type H = H;
+
+    fn mix_root(channel: &mut Self::C, root: H::Hash) {
+        channel.queue.send(ProverMessage::Root(root.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::iter::zip;
+
+    use itertools::Itertools;
+    use num_traits::Zero;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{
+        ChallengeError, InteractiveChannel, InteractiveMerkleChannel, MessageQueue, ProverMessage,
+    };
+    use crate::backend::CpuBackend;
+    use crate::channel::{ChallengeSource, MerkleChannel};
+    use crate::circle::CirclePoint;
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::fields::secure_column::SECURE_EXTENSION_DEGREE;
+    use crate::fri::{
+        fold_circle_into_line, fold_line, CirclePolyDegreeBound, FriConfig, FriLayerProof,
+        FriProof, FriVerifier, CIRCLE_TO_LINE_FOLD_STEP, FOLD_STEP,
+    };
+    use crate::pcs::quotients::{fri_answers, PointSample};
+    use crate::pcs::{CommitmentSchemeProof, CommitmentSchemeVerifier, PcsConfig, TreeVec};
+    use crate::poly::circle::{CanonicCoset, CirclePoly, SecureEvaluation};
+    use crate::poly::line::{LineDomain, LineEvaluation, LinePoly};
+    use crate::poly::BitReversedOrder;
+    use crate::queries::Queries;
+    use crate::vcs::blake2_hash::Blake2sHash;
+    use crate::vcs::blake2_merkle::Blake2sMerkleHasher;
+    use crate::vcs::prover::{MerkleDecommitment, MerkleProver};
+    use crate::{ColumnVec, VerificationError};
+
+    fn random_felt(rng: &mut SmallRng) -> SecureField {
+        SecureField::from_m31_array(core::array::from_fn(|_| {
+            BaseField::from(rng.gen_range(0..1 << 30))
+        }))
+    }
+
+    /// An event seen by the [InMemoryProver], in order.
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Message(ProverMessage),
+        Felts(usize),
+        RandomBytes,
+    }
+
+    /// A message queue that records what the verifier sends and answers with random challenges.
+    /// It also commits to random columns for the verifier to query.
+    struct InMemoryProver {
+        columns: Vec<Vec<BaseField>>,
+        rng: SmallRng,
+        events: Vec<Event>,
+    }
+
+    impl InMemoryProver {
+        fn new(log_sizes: &[u32]) -> Self {
+            let mut rng = SmallRng::seed_from_u64(0);
+            let columns = log_sizes
+                .iter()
+                .map(|&log_size| {
+                    (0..1 << log_size)
+                        .map(|_| BaseField::from(rng.gen_range(0..1 << 30)))
+                        .collect()
+                })
+                .collect();
+            Self {
+                columns,
+                rng,
+                events: vec![],
+            }
+        }
+
+        fn merkle(&self) -> MerkleProver<CpuBackend, Blake2sMerkleHasher> {
+            MerkleProver::commit(self.columns.iter().collect_vec())
+        }
+
+        fn answer_queries(
+            &self,
+            queries: &BTreeMap<u32, Vec<usize>>,
+        ) -> (Vec<BaseField>, MerkleDecommitment<Blake2sMerkleHasher>) {
+            self.merkle()
+                .decommit(queries, self.columns.iter().collect_vec())
+        }
+    }
+
+    impl MessageQueue for InMemoryProver {
+        fn send(&mut self, message: ProverMessage) {
+            self.events.push(Event::Message(message));
+        }
+
+        fn receive_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+            self.events.push(Event::Felts(n_felts));
+            (0..n_felts).map(|_| random_felt(&mut self.rng)).collect()
+        }
+
+        fn receive_random_bytes(&mut self) -> Vec<u8> {
+            self.events.push(Event::RandomBytes);
+            (0..32).map(|_| self.rng.gen()).collect()
+        }
+    }
+
+    type TestMerkleChannel = InteractiveMerkleChannel<Blake2sMerkleHasher, InMemoryProver>;
+
+    /// An entry of a [Beacon] transcript.
+    #[derive(Clone, Debug, PartialEq)]
+    enum BeaconEntry {
+        Message(ProverMessage),
+        Felts(Vec<SecureField>),
+        RandomBytes(Vec<u8>),
+    }
+
+    /// A public randomness beacon.
+    ///
+    /// The prover draws fresh challenges from it, which are recorded along with the prover's
+    /// messages. The verifier then replays the transcript: each challenge is only released after
+    /// the verifier sent the same messages as the prover did before drawing it.
+    struct Beacon {
+        rng: SmallRng,
+        transcript: Vec<BeaconEntry>,
+        /// The number of entries the verifier replayed, or [None] while recording.
+        n_replayed: Option<usize>,
+    }
+
+    impl Beacon {
+        fn new() -> Self {
+            Self {
+                rng: SmallRng::seed_from_u64(0),
+                transcript: vec![],
+                n_replayed: None,
+            }
+        }
+
+        fn replay(self) -> Self {
+            Self {
+                n_replayed: Some(0),
+                ..self
+            }
+        }
+
+        /// Draws and records a challenge, or returns the next recorded one when replaying.
+        fn release(&mut self, draw: impl FnOnce(&mut SmallRng) -> BeaconEntry) -> BeaconEntry {
+            match &mut self.n_replayed {
+                None => {
+                    let entry = draw(&mut self.rng);
+                    self.transcript.push(entry.clone());
+                    entry
+                }
+                Some(n_replayed) => {
+                    *n_replayed += 1;
+                    self.transcript[*n_replayed - 1].clone()
+                }
+            }
+        }
+    }
+
+    impl MessageQueue for Beacon {
+        fn send(&mut self, message: ProverMessage) {
+            let entry = BeaconEntry::Message(message);
+            match &mut self.n_replayed {
+                None => self.transcript.push(entry),
+                Some(n_replayed) => {
+                    assert_eq!(self.transcript.get(*n_replayed), Some(&entry));
+                    *n_replayed += 1;
+                }
+            }
+        }
+
+        fn receive_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+            match self
+                .release(|rng| BeaconEntry::Felts((0..n_felts).map(|_| random_felt(rng)).collect()))
+            {
+                BeaconEntry::Felts(felts) => felts,
+                entry => panic!("expected felts, found {entry:?}"),
+            }
+        }
+
+        fn receive_random_bytes(&mut self) -> Vec<u8> {
+            match self.release(|rng| BeaconEntry::RandomBytes((0..32).map(|_| rng.gen()).collect()))
+            {
+                BeaconEntry::RandomBytes(bytes) => bytes,
+                entry => panic!("expected random bytes, found {entry:?}"),
+            }
+        }
+    }
+
+    /// A message queue that answers every draw with an empty challenge.
+    struct MuteQueue;
+
+    impl MessageQueue for MuteQueue {
+        fn send(&mut self, _message: ProverMessage) {}
+
+        fn receive_felts(&mut self, _n_felts: usize) -> Vec<SecureField> {
+            vec![]
+        }
+
+        fn receive_random_bytes(&mut self) -> Vec<u8> {
+            vec![]
+        }
+    }
+
+    type BeaconMerkleChannel = InteractiveMerkleChannel<Blake2sMerkleHasher, Beacon>;
+
+    const LOG_SIZE: u32 = 4;
+    const LOG_BLOWUP_FACTOR: u32 = 1;
+
+    fn opening_config() -> PcsConfig {
+        // A single inner FRI layer.
+        PcsConfig {
+            pow_bits: 0,
+            fri_config: FriConfig::new(LOG_SIZE - 2, LOG_BLOWUP_FACTOR, 3),
+            grinding: vec![],
+        }
+    }
+
+    /// Two trees, of two and one columns.
+    fn opening_polys() -> Vec<Vec<CirclePoly<CpuBackend>>> {
+        let mut rng = SmallRng::seed_from_u64(1);
+        [2, 1]
+            .map(|n_columns| {
+                (0..n_columns)
+                    .map(|_| {
+                        CirclePoly::new(
+                            (0..1 << LOG_SIZE)
+                                .map(|_| BaseField::from(rng.gen_range(0..1 << 30)))
+                                .collect(),
+                        )
+                    })
+                    .collect()
+            })
+            .to_vec()
+    }
+
+    /// Opens the first column at two points, and the others at `oods_point` only.
+    fn opening_points(
+        oods_point: CirclePoint<SecureField>,
+    ) -> TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>> {
+        TreeVec(vec![
+            vec![vec![oods_point, oods_point.double()], vec![oods_point]],
+            vec![vec![oods_point]],
+        ])
+    }
+
+    /// A FRI layer committed by [prove_opening].
+    struct FriLayer {
+        values: Vec<SecureField>,
+        columns: Vec<Vec<BaseField>>,
+        merkle: MerkleProver<CpuBackend, Blake2sMerkleHasher>,
+    }
+
+    impl FriLayer {
+        /// Commits to bit-reversed evaluations of the layer.
+        fn commit(values: Vec<SecureField>) -> Self {
+            let columns = (0..SECURE_EXTENSION_DEGREE)
+                .map(|i| values.iter().map(|value| value.to_m31_array()[i]).collect())
+                .collect_vec();
+            let merkle = MerkleProver::commit(columns.iter().collect_vec());
+            Self {
+                values,
+                columns,
+                merkle,
+            }
+        }
+
+        /// Decommits the pairs of evaluations that fold into the folded queries. The evaluations
+        /// the verifier doesn't know from the queries go into the witness.
+        fn decommit(&self, queries: &Queries) -> FriLayerProof<Blake2sMerkleHasher> {
+            let mut positions = vec![];
+            let mut fri_witness = vec![];
+            for subset in queries.chunk_by(|a, b| a >> FOLD_STEP == b >> FOLD_STEP) {
+                let subset_start = (subset[0] >> FOLD_STEP) << FOLD_STEP;
+                for position in subset_start..subset_start + (1 << FOLD_STEP) {
+                    positions.push(position);
+                    if !subset.contains(&position) {
+                        fri_witness.push(self.values[position]);
+                    }
+                }
+            }
+            let (_, decommitment) = self.merkle.decommit(
+                &BTreeMap::from_iter([(queries.log_domain_size, positions)]),
+                self.columns.iter().collect_vec(),
+            );
+            FriLayerProof {
+                fri_witness,
+                decommitment,
+                commitment: self.merkle.root(),
+            }
+        }
+    }
+
+    /// Proves the opening of [opening_polys] at [opening_points] step by step, drawing each
+    /// challenge from the beacon as soon as the messages it depends on are sent.
+    fn prove_opening(
+        channel: &mut InteractiveChannel<Beacon>,
+    ) -> CommitmentSchemeProof<Blake2sMerkleHasher> {
+        let config = opening_config();
+        let domain = CanonicCoset::new(LOG_SIZE + LOG_BLOWUP_FACTOR).circle_domain();
+        let polys = opening_polys();
+        let columns = polys
+            .iter()
+            .map(|polys| {
+                polys
+                    .iter()
+                    .map(|poly| poly.evaluate(domain).values)
+                    .collect_vec()
+            })
+            .collect_vec();
+        let trees = columns
+            .iter()
+            .map(|columns| {
+                MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(
+                    columns.iter().collect_vec(),
+                )
+            })
+            .collect_vec();
+        for tree in &trees {
+            BeaconMerkleChannel::mix_root(channel, tree.root());
+        }
+
+        let sampled_points = opening_points(CirclePoint::get_random_point(channel));
+        let sampled_values = TreeVec(
+            zip(&polys, sampled_points.iter())
+                .map(|(polys, points)| {
+                    zip(polys, points)
+                        .map(|(poly, points)| {
+                            points
+                                .iter()
+                                .map(|&point| poly.eval_at_point(point))
+                                .collect()
+                        })
+                        .collect()
+                })
+                .collect(),
+        );
+        channel.mix_felts(&sampled_values.clone().flatten_cols());
+        let random_coeff = channel.draw_felt();
+
+        // The quotients are the answers to the queries of all positions of the domain.
+        let all_positions =
+            BTreeMap::from_iter([(domain.log_size(), (0..domain.size()).collect_vec())]);
+        let n_columns_per_log_size = columns
+            .iter()
+            .map(|columns| BTreeMap::from_iter([(domain.log_size(), columns.len())]))
+            .collect_vec();
+        let [quotients] = fri_answers(
+            TreeVec(
+                columns
+                    .iter()
+                    .map(|columns| vec![domain.log_size(); columns.len()])
+                    .collect(),
+            ),
+            sampled_points
+                .clone()
+                .zip_cols(sampled_values.clone())
+                .map_cols(|(points, values)| {
+                    zip(points, values)
+                        .map(|(point, value)| PointSample { point, value })
+                        .collect()
+                }),
+            random_coeff,
+            &all_positions,
+            TreeVec(
+                zip(&trees, &columns)
+                    .map(|(tree, columns)| {
+                        tree.decommit(&all_positions, columns.iter().collect()).0
+                    })
+                    .collect(),
+            ),
+            TreeVec(n_columns_per_log_size.iter().collect()),
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        let first_layer = FriLayer::commit(quotients);
+        BeaconMerkleChannel::mix_root(channel, first_layer.merkle.root());
+        let mut line_evaluation = LineEvaluation::new_zero(LineDomain::new(domain.half_coset));
+        fold_circle_into_line(
+            &mut line_evaluation,
+            &SecureEvaluation::<CpuBackend, BitReversedOrder>::new(
+                domain,
+                first_layer.values.iter().copied().collect(),
+            ),
+            channel.draw_felt(),
+        );
+        let inner_layer = FriLayer::commit(line_evaluation.values.to_vec());
+        BeaconMerkleChannel::mix_root(channel, inner_layer.merkle.root());
+        let mut last_layer_coeffs = fold_line(&line_evaluation, channel.draw_felt())
+            .interpolate()
+            .into_ordered_coefficients();
+        let zeros = last_layer_coeffs.split_off(1 << config.fri_config.log_last_layer_degree_bound);
+        assert!(zeros.iter().all(SecureField::is_zero));
+        let last_layer_poly = LinePoly::from_ordered_coefficients(last_layer_coeffs);
+        channel.mix_felts(&last_layer_poly);
+
+        let proof_of_work = 0;
+        channel.mix_u64(proof_of_work);
+        let queries = Queries::generate(channel, domain.log_size(), config.fri_config.n_queries);
+        let query_positions = BTreeMap::from_iter([(domain.log_size(), queries.positions.clone())]);
+        let (queried_values, decommitments) = zip(&trees, &columns)
+            .map(|(tree, columns)| tree.decommit(&query_positions, columns.iter().collect()))
+            .unzip();
+
+        CommitmentSchemeProof {
+            config,
+            commitments: TreeVec(trees.iter().map(MerkleProver::root).collect()),
+            sampled_values,
+            decommitments: TreeVec(decommitments),
+            queried_values: TreeVec(queried_values),
+            proof_of_work,
+            grinding_nonces: vec![],
+            fri_proof: FriProof {
+                first_layer: first_layer.decommit(&queries),
+                inner_layers: vec![inner_layer.decommit(&queries.fold(CIRCLE_TO_LINE_FOLD_STEP))],
+                last_layer_poly,
+            },
+        }
+    }
+
+    fn verify_opening(
+        channel: &mut InteractiveChannel<Beacon>,
+        proof: CommitmentSchemeProof<Blake2sMerkleHasher>,
+    ) -> Result<(), VerificationError> {
+        let mut commitment_scheme =
+            CommitmentSchemeVerifier::<BeaconMerkleChannel>::new(opening_config());
+        for (&root, columns) in zip(proof.commitments.iter(), proof.sampled_values.iter()) {
+            commitment_scheme.commit(root, &vec![LOG_SIZE; columns.len()], channel);
+        }
+        let sampled_points = opening_points(CirclePoint::get_random_point(channel));
+        commitment_scheme.verify_values(sampled_points, proof, channel)
+    }
+
+    #[test]
+    fn test_interactive_commitment_and_queries() {
+        // Columns are committed on their evaluation domain, with a blowup factor of 2.
+        let log_sizes = [4, 2, 4];
+        let channel = &mut InteractiveChannel::new(InMemoryProver::new(&[5, 3, 5]));
+        let mut commitment_scheme = CommitmentSchemeVerifier::<TestMerkleChannel>::new(PcsConfig {
+            pow_bits: 0,
+            fri_config: FriConfig::new(0, 1, 4),
+            grinding: vec![],
+        });
+        let root = channel.queue.merkle().root();
+
+        commitment_scheme.commit(root, &log_sizes, channel);
+        let queries = Queries::generate(channel, 5, 4);
+        let queries_per_log_size = BTreeMap::from_iter([
+            (5, queries.positions.clone()),
+            (3, queries.fold(2).positions),
+        ]);
+        let (queried_values, decommitment) = channel.queue.answer_queries(&queries_per_log_size);
+
+        commitment_scheme.trees[0]
+            .verify(&queries_per_log_size, queried_values, decommitment)
+            .unwrap();
+        assert_eq!(
+            channel.queue.events,
+            [
+                Event::Message(ProverMessage::Root(root.to_string())),
+                Event::RandomBytes
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interactive_fri_commitment() {
+        let channel = &mut InteractiveChannel::new(InMemoryProver::new(&[]));
+        let layer_proof = |commitment| FriLayerProof::<Blake2sMerkleHasher> {
+            fri_witness: vec![],
+            decommitment: MerkleDecommitment {
+                hash_witness: vec![],
+                column_witness: vec![],
+            },
+            commitment,
+        };
+        let last_layer_coeff = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let proof = FriProof {
+            first_layer: layer_proof(Blake2sHash([1; 32])),
+            inner_layers: vec![layer_proof(Blake2sHash([2; 32]))],
+            last_layer_poly: LinePoly::new(vec![last_layer_coeff]),
+        };
+
+        let mut fri_verifier = FriVerifier::<TestMerkleChannel>::commit(
+            channel,
+            FriConfig::new(0, 1, 3),
+            proof,
+            vec![CirclePolyDegreeBound::new(2)],
+        )
+        .unwrap();
+        let query_positions = fri_verifier.sample_query_positions(channel);
+
+        assert_eq!(query_positions.keys().collect_vec(), [&3]);
+        assert_eq!(
+            channel.queue.events,
+            [
+                Event::Message(ProverMessage::Root(Blake2sHash([1; 32]).to_string())),
+                Event::Felts(1),
+                Event::Message(ProverMessage::Root(Blake2sHash([2; 32]).to_string())),
+                Event::Felts(1),
+                Event::Message(ProverMessage::Felts(vec![last_layer_coeff])),
+                Event::RandomBytes,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interactive_verify_values() {
+        let mut prover_channel = InteractiveChannel::new(Beacon::new());
+        let proof = prove_opening(&mut prover_channel);
+        let channel = &mut InteractiveChannel::new(prover_channel.queue.replay());
+
+        verify_opening(channel, proof).unwrap();
+        assert_eq!(
+            channel.queue.n_replayed,
+            Some(channel.queue.transcript.len())
+        );
+    }
+
+    #[test]
+    fn test_interactive_verify_values_malformed_challenge() {
+        let mut prover_channel = InteractiveChannel::new(Beacon::new());
+        let proof = prove_opening(&mut prover_channel);
+        let mut beacon = prover_channel.queue.replay();
+        // Withhold the folding randomness of the inner FRI layer.
+        let folding_alpha = beacon
+            .transcript
+            .iter_mut()
+            .rev()
+            .find_map(|entry| match entry {
+                BeaconEntry::Felts(felts) => Some(felts),
+                _ => None,
+            })
+            .unwrap();
+        folding_alpha.clear();
+
+        let error = verify_opening(&mut InteractiveChannel::new(beacon), proof).unwrap_err();
+
+        assert!(matches!(
+            error,
+            VerificationError::Challenge(ChallengeError::WrongFeltCount {
+                expected: 1,
+                actual: 0
+            })
+        ));
+        assert_eq!(error.code(), 400);
+    }
+
+    #[test]
+    fn test_interactive_channel_rejects_malformed_challenges() {
+        let channel = &mut InteractiveChannel::new(MuteQueue);
+
+        assert_eq!(channel.draw_felt(), SecureField::zero());
+        assert_eq!(
+            channel.malformed_challenge(),
+            Some(ChallengeError::WrongFeltCount {
+                expected: 1,
+                actual: 0
+            })
+        );
+        // Query sampling terminates, and the first malformed challenge is kept.
+        assert_eq!(Queries::generate(channel, 4, 2).positions, [0]);
+        assert_eq!(
+            channel.malformed_challenge(),
+            Some(ChallengeError::WrongFeltCount {
+                expected: 1,
+                actual: 0
+            })
+        );
+
+        let channel = &mut InteractiveChannel::new(MuteQueue);
+        Queries::generate(channel, 4, 2);
+        assert_eq!(
+            channel.malformed_challenge(),
+            Some(ChallengeError::MalformedRandomBytes { actual: 0 })
+        );
+    }
+}
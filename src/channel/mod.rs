@@ -3,8 +3,17 @@ use alloc::vec::Vec;
 use crate::{fields::qm31::SecureField, vcs::ops::MerkleHasher};
 
 pub use blake2s::Blake2sChannel;
+pub use interactive::{
+    ChallengeError, InteractiveChannel, InteractiveMerkleChannel, MessageQueue, ProverMessage,
+};
+pub use transcript::{
+    RecordingChannel, RecordingMerkleChannel, ReplayChannel, ReplayMerkleChannel,
+    TranscriptDivergence, TranscriptEntry,
+};
 
 pub mod blake2s;
+pub mod interactive;
+pub mod transcript;
 
 pub trait Channel: Default + Clone {
     const BYTES_PER_HASH: usize;
@@ -14,6 +23,9 @@ pub trait Channel: Default + Clone {
     // Mix functions.
     fn mix_felts(&mut self, felts: &[SecureField]);
     fn mix_u64(&mut self, value: u64);
+    fn mix_u32s(&mut self, data: &[u32]);
+    /// Mixes raw bytes, such as a hash of public inputs.
+    fn mix_bytes(&mut self, data: &[u8]);
 
     // Draw functions.
     fn draw_felt(&mut self) -> SecureField;
@@ -21,10 +33,68 @@ pub trait Channel: Default + Clone {
     fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField>;
     /// Returns a vector of random bytes of length `BYTES_PER_HASH`.
     fn draw_random_bytes(&mut self) -> Vec<u8>;
+    /// Returns a vector of random u32s, read from `BYTES_PER_HASH` random bytes.
+    fn draw_u32s(&mut self) -> Vec<u32>;
+}
+
+/// The source of the verifier's challenges.
+///
+/// The verifier reports every prover message it absorbs with the `mix_*` functions, and obtains
+/// its challenges with the `draw_*` functions. Every [Channel] is a challenge source that derives
+/// the challenges with Fiat-Shamir. [InteractiveChannel] instead takes them from an external
+/// [MessageQueue], e.g. for interactive verification or a public randomness beacon.
+pub trait ChallengeSource {
+    /// Returns the number of trailing zeros of the current proof of work.
+    fn trailing_zeros(&self) -> u32;
+
+    // Mix functions.
+    fn mix_felts(&mut self, felts: &[SecureField]);
+    fn mix_u64(&mut self, value: u64);
+
+    // Draw functions.
+    fn draw_felt(&mut self) -> SecureField;
+    /// Returns a vector of `n_felts` random SecureField elements.
+    fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField>;
+    /// Returns a vector of random bytes.
+    fn draw_random_bytes(&mut self) -> Vec<u8>;
+
+    /// Returns the first malformed challenge drawn so far, if any.
+    ///
+    /// Draws can't fail, so a source that takes its challenges from outside the verifier records
+    /// malformed ones here. The verifier checks it once it's done.
+    fn malformed_challenge(&self) -> Option<ChallengeError> {
+        None
+    }
+}
+
+impl<C: Channel> ChallengeSource for C {
+    fn trailing_zeros(&self) -> u32 {
+        Channel::trailing_zeros(self)
+    }
+
+    fn mix_felts(&mut self, felts: &[SecureField]) {
+        Channel::mix_felts(self, felts)
+    }
+
+    fn mix_u64(&mut self, value: u64) {
+        Channel::mix_u64(self, value)
+    }
+
+    fn draw_felt(&mut self) -> SecureField {
+        Channel::draw_felt(self)
+    }
+
+    fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+        Channel::draw_felts(self, n_felts)
+    }
+
+    fn draw_random_bytes(&mut self) -> Vec<u8> {
+        Channel::draw_random_bytes(self)
+    }
 }
 
 pub trait MerkleChannel: Default {
-    type C: Channel;
+    type C: ChallengeSource;
     type H: MerkleHasher;
     fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash);
 }
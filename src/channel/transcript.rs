@@ -0,0 +1,340 @@
+//! Channel wrappers for debugging Fiat-Shamir transcripts.
+//!
+//! [RecordingChannel] logs every value mixed into and drawn from a channel. [ReplayChannel] runs
+//! against a previously recorded transcript, e.g. one recorded on the prover side, and reports the
+//! first operation at which the two runs diverge.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Channel, MerkleChannel};
+use crate::fields::qm31::SecureField;
+use crate::vcs::ops::MerkleHasher;
+
+/// A single channel operation, with its inputs (for mixes) or outputs (for draws).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptEntry {
+    MixFelts(Vec<SecureField>),
+    MixU64(u64),
+    MixU32s(Vec<u32>),
+    MixBytes(Vec<u8>),
+    /// A Merkle root, formatted with its [Display](core::fmt::Display) implementation.
+    MixRoot(String),
+    DrawFelt(SecureField),
+    DrawFelts(Vec<SecureField>),
+    DrawRandomBytes(Vec<u8>),
+    DrawU32s(Vec<u32>),
+}
+
+/// A [Channel] that forwards every operation to `C` and records it.
+#[derive(Clone, Default)]
+pub struct RecordingChannel<C: Channel> {
+    pub channel: C,
+    pub transcript: Vec<TranscriptEntry>,
+}
+
+impl<C: Channel> RecordingChannel<C> {
+    pub const fn new(channel: C) -> Self {
+        Self {
+            channel,
+            transcript: Vec::new(),
+        }
+    }
+}
+
+impl<C: Channel> Channel for RecordingChannel<C> {
+    const BYTES_PER_HASH: usize = C::BYTES_PER_HASH;
+
+    fn trailing_zeros(&self) -> u32 {
+        self.channel.trailing_zeros()
+    }
+
+    fn mix_felts(&mut self, felts: &[SecureField]) {
+        self.channel.mix_felts(felts);
+        self.transcript
+            .push(TranscriptEntry::MixFelts(felts.to_vec()));
+    }
+
+    fn mix_u64(&mut self, value: u64) {
+        self.channel.mix_u64(value);
+        self.transcript.push(TranscriptEntry::MixU64(value));
+    }
+
+    fn mix_u32s(&mut self, data: &[u32]) {
+        self.channel.mix_u32s(data);
+        self.transcript
+            .push(TranscriptEntry::MixU32s(data.to_vec()));
+    }
+
+    fn mix_bytes(&mut self, data: &[u8]) {
+        self.channel.mix_bytes(data);
+        self.transcript
+            .push(TranscriptEntry::MixBytes(data.to_vec()));
+    }
+
+    fn draw_felt(&mut self) -> SecureField {
+        let felt = self.channel.draw_felt();
+        self.transcript.push(TranscriptEntry::DrawFelt(felt));
+        felt
+    }
+
+    fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+        let felts = self.channel.draw_felts(n_felts);
+        self.transcript
+            .push(TranscriptEntry::DrawFelts(felts.clone()));
+        felts
+    }
+
+    fn draw_random_bytes(&mut self) -> Vec<u8> {
+        let bytes = self.channel.draw_random_bytes();
+        self.transcript
+            .push(TranscriptEntry::DrawRandomBytes(bytes.clone()));
+        bytes
+    }
+
+    fn draw_u32s(&mut self) -> Vec<u32> {
+        let words = self.channel.draw_u32s();
+        self.transcript
+            .push(TranscriptEntry::DrawU32s(words.clone()));
+        words
+    }
+}
+
+/// A [MerkleChannel] over a [RecordingChannel], which also records the mixed roots.
+#[derive(Default)]
+pub struct RecordingMerkleChannel<MC: MerkleChannel>(PhantomData<MC>);
+
+impl<MC: MerkleChannel> MerkleChannel for RecordingMerkleChannel<MC>
+where
+    MC::C: Channel,
+{
+    type C = RecordingChannel<MC::C>;
+    type H = MC::H;
+
+    fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash) {
+        MC::mix_root(&mut channel.channel, root);
+        channel
+            .transcript
+            .push(TranscriptEntry::MixRoot(root.to_string()));
+    }
+}
+
+/// The first operation at which a run diverged from the expected transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptDivergence {
+    /// Index of the operation in the transcript.
+    pub index: usize,
+    /// The recorded entry, or [None] if the run has more operations than the transcript.
+    pub expected: Option<TranscriptEntry>,
+    /// The entry of the run, or [None] if the run ended before the transcript.
+    pub actual: Option<TranscriptEntry>,
+}
+
+/// A [Channel] that forwards every operation to `C` and compares it to an expected transcript.
+#[derive(Clone, Default)]
+pub struct ReplayChannel<C: Channel> {
+    recording: RecordingChannel<C>,
+    expected: Vec<TranscriptEntry>,
+}
+
+impl<C: Channel> ReplayChannel<C> {
+    pub const fn new(channel: C, expected: Vec<TranscriptEntry>) -> Self {
+        Self {
+            recording: RecordingChannel::new(channel),
+            expected,
+        }
+    }
+
+    /// Returns the first divergence between the operations so far and the expected transcript.
+    ///
+    /// Running out of expected entries is a divergence, but not having reached the end of the
+    /// transcript yet is not. Use [ReplayChannel::finish] once the run is complete.
+    pub fn first_divergence(&self) -> Option<TranscriptDivergence> {
+        let actual = &self.recording.transcript;
+        let index = actual
+            .iter()
+            .zip(&self.expected)
+            .position(|(actual, expected)| actual != expected)
+            .or_else(|| (actual.len() > self.expected.len()).then_some(self.expected.len()))?;
+        Some(TranscriptDivergence {
+            index,
+            expected: self.expected.get(index).cloned(),
+            actual: actual.get(index).cloned(),
+        })
+    }
+
+    /// Checks that the run matched the whole expected transcript.
+    pub fn finish(self) -> Result<(), TranscriptDivergence> {
+        if let Some(divergence) = self.first_divergence() {
+            return Err(divergence);
+        }
+        let index = self.recording.transcript.len();
+        match self.expected.get(index) {
+            Some(expected) => Err(TranscriptDivergence {
+                index,
+                expected: Some(expected.clone()),
+                actual: None,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<C: Channel> Channel for ReplayChannel<C> {
+    const BYTES_PER_HASH: usize = C::BYTES_PER_HASH;
+
+    fn trailing_zeros(&self) -> u32 {
+        self.recording.trailing_zeros()
+    }
+
+    fn mix_felts(&mut self, felts: &[SecureField]) {
+        self.recording.mix_felts(felts)
+    }
+
+    fn mix_u64(&mut self, value: u64) {
+        self.recording.mix_u64(value)
+    }
+
+    fn mix_u32s(&mut self, data: &[u32]) {
+        self.recording.mix_u32s(data)
+    }
+
+    fn mix_bytes(&mut self, data: &[u8]) {
+        self.recording.mix_bytes(data)
+    }
+
+    fn draw_felt(&mut self) -> SecureField {
+        self.recording.draw_felt()
+    }
+
+    fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+        self.recording.draw_felts(n_felts)
+    }
+
+    fn draw_random_bytes(&mut self) -> Vec<u8> {
+        self.recording.draw_random_bytes()
+    }
+
+    fn draw_u32s(&mut self) -> Vec<u32> {
+        self.recording.draw_u32s()
+    }
+}
+
+/// A [MerkleChannel] over a [ReplayChannel], which also checks the mixed roots.
+#[derive(Default)]
+pub struct ReplayMerkleChannel<MC: MerkleChannel>(PhantomData<MC>);
+
+impl<MC: MerkleChannel> MerkleChannel for ReplayMerkleChannel<MC>
+where
+    MC::C: Channel,
+{
+    type C = ReplayChannel<MC::C>;
+    type H = MC::H;
+
+    fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash) {
+        RecordingMerkleChannel::<MC>::mix_root(&mut channel.recording, root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use super::{
+        RecordingChannel, RecordingMerkleChannel, ReplayChannel, ReplayMerkleChannel,
+        TranscriptDivergence, TranscriptEntry,
+    };
+    use crate::channel::{Blake2sChannel, ChallengeSource, MerkleChannel};
+    use crate::fields::qm31::SecureField;
+    use crate::vcs::blake2_hash::Blake2sHash;
+    use crate::vcs::blake2_merkle::Blake2sMerkleChannel;
+
+    /// Runs a short protocol, mixing `nonce` in the middle, and returns everything drawn.
+    fn run<MC: MerkleChannel>(channel: &mut MC::C, nonce: u64) -> Vec<SecureField>
+    where
+        MC::H: crate::vcs::ops::MerkleHasher<Hash = Blake2sHash>,
+    {
+        MC::mix_root(channel, Blake2sHash([7; 32]));
+        let mut drawn = channel.draw_felts(3);
+        channel.mix_u64(nonce);
+        drawn.push(channel.draw_felt());
+        channel.mix_felts(&drawn);
+        channel.draw_random_bytes();
+        drawn
+    }
+
+    #[test]
+    fn test_recording_channel_is_transparent() {
+        let mut channel = Blake2sChannel::default();
+        let mut recording_channel = RecordingChannel::<Blake2sChannel>::default();
+
+        let drawn = run::<Blake2sMerkleChannel>(&mut channel, 5);
+        let recorded_drawn =
+            run::<RecordingMerkleChannel<Blake2sMerkleChannel>>(&mut recording_channel, 5);
+
+        assert_eq!(drawn, recorded_drawn);
+        assert_eq!(channel.digest(), recording_channel.channel.digest());
+        assert_eq!(recording_channel.transcript.len(), 6);
+        assert_eq!(
+            recording_channel.transcript[0],
+            TranscriptEntry::MixRoot(Blake2sHash([7; 32]).to_string())
+        );
+        assert_eq!(recording_channel.transcript[2], TranscriptEntry::MixU64(5));
+    }
+
+    #[test]
+    fn test_replay_channel_matches() {
+        let mut recording_channel = RecordingChannel::<Blake2sChannel>::default();
+        run::<RecordingMerkleChannel<Blake2sMerkleChannel>>(&mut recording_channel, 5);
+        let mut replay_channel =
+            ReplayChannel::new(Blake2sChannel::default(), recording_channel.transcript);
+
+        run::<ReplayMerkleChannel<Blake2sMerkleChannel>>(&mut replay_channel, 5);
+
+        assert_eq!(replay_channel.finish(), Ok(()));
+    }
+
+    #[test]
+    fn test_replay_channel_reports_first_divergence() {
+        let mut recording_channel = RecordingChannel::<Blake2sChannel>::default();
+        run::<RecordingMerkleChannel<Blake2sMerkleChannel>>(&mut recording_channel, 5);
+        let transcript = recording_channel.transcript;
+        let mut replay_channel = ReplayChannel::new(Blake2sChannel::default(), transcript.clone());
+
+        run::<ReplayMerkleChannel<Blake2sMerkleChannel>>(&mut replay_channel, 6);
+
+        assert_eq!(
+            replay_channel.first_divergence(),
+            Some(TranscriptDivergence {
+                index: 2,
+                expected: Some(TranscriptEntry::MixU64(5)),
+                actual: Some(TranscriptEntry::MixU64(6)),
+            })
+        );
+    }
+
+    #[test]
+    fn test_replay_channel_reports_early_end() {
+        let mut recording_channel = RecordingChannel::<Blake2sChannel>::default();
+        run::<RecordingMerkleChannel<Blake2sMerkleChannel>>(&mut recording_channel, 5);
+        let transcript = recording_channel.transcript;
+        let mut replay_channel = ReplayChannel::new(Blake2sChannel::default(), transcript.clone());
+
+        replay_channel.mix_u64(1);
+        assert_eq!(replay_channel.first_divergence().unwrap().index, 0);
+        let replay_channel = ReplayChannel::new(Blake2sChannel::default(), transcript.clone());
+
+        assert_eq!(
+            replay_channel.finish(),
+            Err(TranscriptDivergence {
+                index: 0,
+                expected: Some(transcript[0].clone()),
+                actual: None,
+            })
+        );
+    }
+}
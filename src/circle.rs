@@ -5,7 +5,7 @@ use num_traits::{One, Zero};
 use super::fields::m31::{BaseField, M31};
 use super::fields::qm31::SecureField;
 use super::fields::{ComplexConjugate, Field, FieldExpOps};
-use crate::channel::Channel;
+use crate::channel::ChallengeSource;
 use crate::fields::qm31::P4;
 use crate::utils::egcd;
 
@@ -168,7 +168,7 @@ impl CirclePoint<SecureField> {
         SECURE_FIELD_CIRCLE_GEN.mul(index)
     }
 
-    pub fn get_random_point<C: Channel>(channel: &mut C) -> Self {
+    pub fn get_random_point<C: ChallengeSource>(channel: &mut C) -> Self {
         let t = channel.draw_felt();
         let t_square = t.square();
 
@@ -0,0 +1,700 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::ops::Mul;
+
+use indexmap_nostd::IndexMap as HashMap;
+use num_traits::{One, Zero};
+
+use super::logup::LogupAtRow;
+use super::periodic_columns::PeriodicColumn;
+use super::{ConstraintDomain, DegreeEvaluator, EvalAtRow, FrameworkEval, INTERACTION_TRACE_IDX};
+use crate::fields::m31::BaseField;
+use crate::fields::qm31::SecureField;
+use crate::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::fields::FieldExpOps;
+use crate::lookups::utils::Fraction;
+use crate::pcs::TreeVec;
+use crate::ColumnVec;
+
+/// A failed check of a component's constraints on its trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstraintFailure {
+    /// A constraint doesn't vanish on a row. Constraints are indexed in the order the component
+    /// adds them, with the logup constraints added when the logup is finalized.
+    Constraint {
+        component: String,
+        constraint_index: usize,
+        row: usize,
+        value: SecureField,
+    },
+    /// A logup fraction has a zero denominator.
+    LogupZeroDenominator { component: String, row: usize },
+    /// The logup fractions of all rows don't sum to the claimed sum.
+    LogupSum {
+        component: String,
+        claimed_sum: SecureField,
+        sum: SecureField,
+    },
+}
+
+impl Display for ConstraintFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Constraint {
+                component,
+                constraint_index,
+                row,
+                value,
+            } => write!(
+                f,
+                "{component}: constraint {constraint_index} is {value} at row {row}"
+            ),
+            Self::LogupZeroDenominator { component, row } => {
+                write!(f, "{component}: logup denominator is zero at row {row}")
+            }
+            Self::LogupSum {
+                component,
+                claimed_sum,
+                sum,
+            } => write!(
+                f,
+                "{component}: logup sum is {sum}, but the claimed sum is {claimed_sum}"
+            ),
+        }
+    }
+}
+
+/// Evaluates constraints at a row of a trace, and records the ones that don't vanish.
+///
+/// Columns are in natural order, i.e. row `i` is the `i`-th point of the column's canonic coset.
+/// Mask offsets wrap around the column size.
+pub struct AssertEvaluator<'a> {
+    pub trace: &'a TreeVec<ColumnVec<&'a Vec<BaseField>>>,
+    pub col_index: TreeVec<usize>,
+    pub row: usize,
+    pub logup: LogupAtRow<Self>,
+    pub failures: Vec<ConstraintFailure>,
+    component: &'a str,
+    constraint_index: usize,
+    constraint_domain: ConstraintDomain,
+}
+
+impl<'a> AssertEvaluator<'a> {
+    pub fn new(
+        component: &'a str,
+        trace: &'a TreeVec<ColumnVec<&'a Vec<BaseField>>>,
+        row: usize,
+        log_size: u32,
+        claimed_sum: SecureField,
+    ) -> Self {
+        Self {
+            trace,
+            col_index: TreeVec::new(vec![0; trace.len()]),
+            row,
+            logup: LogupAtRow::new(INTERACTION_TRACE_IDX, claimed_sum, log_size),
+            failures: vec![],
+            component,
+            constraint_index: 0,
+            constraint_domain: ConstraintDomain::new(log_size),
+        }
+    }
+
+    /// Records a failure if the constraint is checked at the row and doesn't vanish.
+    fn check_constraint(&mut self, is_checked: bool, value: SecureField) {
+        if is_checked && !value.is_zero() {
+            self.failures.push(ConstraintFailure::Constraint {
+                component: self.component.to_string(),
+                constraint_index: self.constraint_index,
+                row: self.row,
+                value,
+            });
+        }
+        self.constraint_index += 1;
+    }
+
+    /// Returns the sum of the logup fractions written at the row.
+    fn logup_sum(&mut self) -> SecureField {
+        let mut sum = SecureField::zero();
+        for fraction in &self.logup.fracs {
+            if fraction.denominator.is_zero() {
+                self.failures.push(ConstraintFailure::LogupZeroDenominator {
+                    component: self.component.to_string(),
+                    row: self.row,
+                });
+                continue;
+            }
+            sum += fraction.numerator * fraction.denominator.inverse();
+        }
+        sum
+    }
+}
+
+impl EvalAtRow for AssertEvaluator<'_> {
+    type F = BaseField;
+    type EF = SecureField;
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        interaction: usize,
+        offsets: [isize; N],
+    ) -> [Self::F; N] {
+        let col_index = self.col_index[interaction];
+        self.col_index[interaction] += 1;
+        let column = &self.trace[interaction][col_index];
+        offsets.map(|offset| {
+            // The mask row might wrap around the column size.
+            let row = (self.row as isize + offset).rem_euclid(column.len() as isize);
+            column[row as usize]
+        })
+    }
+
+    fn get_periodic_column(&mut self, column: &PeriodicColumn) -> Self::F {
+        column.at(self.row)
+    }
+
+    fn set_strided_constraint_domain(&mut self, domain: ConstraintDomain) {
+        self.constraint_domain = domain;
+    }
+
+    fn add_constraint<G>(&mut self, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+        // Constraints on a smaller or strided domain are only checked on its rows.
+        let is_checked = self.constraint_domain.contains(self.row);
+        self.check_constraint(is_checked, SecureField::one() * constraint);
+    }
+
+    fn add_boundary_constraint<G>(&mut self, row: usize, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+        assert!(
+            row < 1 << self.constraint_domain.log_size,
+            "The boundary row is outside the constraint domain"
+        );
+        self.check_constraint(self.row == row, SecureField::one() * constraint);
+    }
+
+    fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
+        SecureField::from_m31_array(values)
+    }
+
+    super::logup_proxy!();
+}
+
+/// Evaluates the constraints of `eval` on every row of its trace, and returns all the failures.
+///
+/// `trace` holds the columns of the component only, in natural order. Besides the constraints, the
+/// logup fractions of all rows are checked to sum to `claimed_sum`.
+pub fn assert_constraints<E: FrameworkEval>(
+    component: &str,
+    eval: &E,
+    trace: &TreeVec<ColumnVec<&Vec<BaseField>>>,
+    claimed_sum: SecureField,
+) -> Result<(), Vec<ConstraintFailure>> {
+    let log_size = eval.log_size();
+    let logup_batching = eval
+        .evaluate(DegreeEvaluator::new(log_size))
+        .logup
+        .batching
+        .clone();
+    let mut failures = vec![];
+    let mut logup_sum = SecureField::zero();
+    for row in 0..1 << log_size {
+        let mut row_eval = AssertEvaluator::new(component, trace, row, log_size, claimed_sum);
+        row_eval.logup.batching = logup_batching.clone();
+        let mut row_eval = eval.evaluate(row_eval);
+        logup_sum += row_eval.logup_sum();
+        failures.append(&mut row_eval.failures);
+    }
+    if logup_sum != claimed_sum {
+        failures.push(ConstraintFailure::LogupSum {
+            component: component.to_string(),
+            claimed_sum,
+            sum: logup_sum,
+        });
+    }
+
+    match failures.is_empty() {
+        true => Ok(()),
+        false => Err(failures),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::ops::{Add, Mul};
+
+    use itertools::Itertools;
+    use num_traits::{One, Zero};
+
+    use super::{assert_constraints, ConstraintFailure};
+    use crate::air::accumulation::PointEvaluationAccumulator;
+    use crate::air::Component;
+    use crate::circle::SECURE_FIELD_CIRCLE_GEN;
+    use crate::constraint_framework::expr::ExprEvaluator;
+    use crate::constraint_framework::logup::{batch_by_degree, LookupElements};
+    use crate::constraint_framework::preprocessed_columns::PreProcessedColumnId;
+    use crate::constraint_framework::{
+        interaction_trace_idx, Degree, DegreeEvaluator, EvalAtRow, FrameworkComponent,
+        FrameworkEval, Relation, RelationEFTraitBound, RelationEntry, TraceLocationAllocator,
+        INTERACTION_TRACE_IDX, PREPROCESSED_TRACE_IDX,
+    };
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::fields::FieldExpOps;
+    use crate::lookups::utils::Fraction;
+    use crate::pcs::TreeVec;
+
+    const LOG_SIZE: u32 = 3;
+
+    struct TestRelation(LookupElements<1>);
+
+    impl<F: Clone, EF: RelationEFTraitBound<F>> Relation<F, EF> for TestRelation {
+        fn combine(&self, values: &[F]) -> EF {
+            self.0.combine(values)
+        }
+
+        fn get_name(&self) -> &str {
+            "TestRelation"
+        }
+
+        fn get_size(&self) -> usize {
+            1
+        }
+    }
+
+    /// A counter that increments by one on every row but the first, and yields its values to a
+    /// relation, with the logup columns in the tree `logup_interaction`.
+    struct CounterEval {
+        relation: TestRelation,
+        logup_interaction: usize,
+    }
+
+    impl FrameworkEval for CounterEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn n_interaction_phases(&self) -> usize {
+            self.logup_interaction - INTERACTION_TRACE_IDX + 1
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            LOG_SIZE + 1
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let is_first = eval.get_preprocessed_column(PreProcessedColumnId {
+                id: "is_first".into(),
+            });
+            let [prev, cur] = eval.next_interaction_mask(1, [-1, 0]);
+            eval.add_constraint((E::F::one() - is_first) * (cur.clone() - prev - E::F::one()));
+            eval.set_logup_interaction(self.logup_interaction);
+            eval.add_to_relation(RelationEntry::new(&self.relation, E::EF::one(), &[cur]));
+            eval.finalize_logup();
+            eval
+        }
+    }
+
+    fn counter_eval() -> CounterEval {
+        CounterEval {
+            relation: TestRelation(LookupElements {
+                z: SecureField::from_u32_unchecked(1, 2, 3, 4),
+                alpha: SecureField::one(),
+                alpha_powers: [SecureField::one()],
+            }),
+            logup_interaction: INTERACTION_TRACE_IDX,
+        }
+    }
+
+    /// Returns the trace of [CounterEval] starting at `start`, and its claimed sum.
+    fn counter_trace(
+        eval: &CounterEval,
+        start: BaseField,
+    ) -> (TreeVec<Vec<Vec<BaseField>>>, SecureField) {
+        let n_rows = 1 << LOG_SIZE;
+        let is_first = (0..n_rows)
+            .map(|row| BaseField::from((row == 0) as u32))
+            .collect_vec();
+        let counter = (0..n_rows)
+            .map(|row| start + BaseField::from(row))
+            .collect_vec();
+        let fractions = counter
+            .iter()
+            .map(|value| {
+                let denominator: SecureField = eval.relation.combine(&[*value]);
+                denominator.inverse()
+            })
+            .collect_vec();
+        let claimed_sum = fractions.iter().copied().sum::<SecureField>();
+        let cumsum_shift = claimed_sum / BaseField::from(n_rows);
+        let mut cumsum = SecureField::zero();
+        let mut interaction = vec![vec![]; 4];
+        for fraction in fractions {
+            cumsum += fraction - cumsum_shift;
+            for (column, value) in interaction.iter_mut().zip(cumsum.to_m31_array()) {
+                column.push(value);
+            }
+        }
+
+        (
+            TreeVec::new(vec![vec![is_first], vec![counter], interaction]),
+            claimed_sum,
+        )
+    }
+
+    #[test]
+    fn test_assert_constraints_on_valid_trace() {
+        let eval = counter_eval();
+        let (trace, claimed_sum) = counter_trace(&eval, BaseField::from(5));
+
+        assert_constraints("counter", &eval, &trace.as_cols_ref(), claimed_sum).unwrap();
+    }
+
+    #[test]
+    fn test_assert_constraints_reports_failures() {
+        let eval = counter_eval();
+        let (mut trace, claimed_sum) = counter_trace(&eval, BaseField::from(5));
+        trace[1][0][3] += BaseField::one();
+
+        let failures =
+            assert_constraints("counter", &eval, &trace.as_cols_ref(), claimed_sum).unwrap_err();
+        let failed_constraints = failures
+            .iter()
+            .filter_map(|failure| match failure {
+                ConstraintFailure::Constraint {
+                    component,
+                    constraint_index,
+                    row,
+                    ..
+                } => Some((component.as_str(), *constraint_index, *row)),
+                _ => None,
+            })
+            .collect_vec();
+
+        // The counter constraint fails at the modified row and the next one, the logup constraint
+        // at the modified row. The modified fraction also changes the logup sum.
+        assert_eq!(
+            failed_constraints,
+            [("counter", 0, 3), ("counter", 1, 3), ("counter", 0, 4)]
+        );
+        assert!(matches!(
+            failures.last(),
+            Some(ConstraintFailure::LogupSum { .. })
+        ));
+    }
+
+    #[test]
+    fn test_assert_constraints_checks_logup_sum() {
+        let eval = counter_eval();
+        let (trace, claimed_sum) = counter_trace(&eval, BaseField::from(5));
+        let wrong_claimed_sum = claimed_sum + SecureField::one();
+
+        let failures =
+            assert_constraints("counter", &eval, &trace.as_cols_ref(), wrong_claimed_sum)
+                .unwrap_err();
+
+        assert_eq!(
+            failures.last(),
+            Some(&ConstraintFailure::LogupSum {
+                component: "counter".into(),
+                claimed_sum: wrong_claimed_sum,
+                sum: claimed_sum,
+            })
+        );
+    }
+
+    #[test]
+    fn test_component_assert_constraints_on_full_trace() {
+        let eval = counter_eval();
+        // The counter wraps around the field modulus.
+        let (trace, claimed_sum) = counter_trace(&eval, -BaseField::from(4));
+        let mut allocator = TraceLocationAllocator::default();
+        let _ = FrameworkComponent::new(&mut allocator, counter_eval(), SecureField::zero());
+        let component = FrameworkComponent::new(&mut allocator, eval, claimed_sum);
+        let (other_trace, _) = counter_trace(&counter_eval(), BaseField::zero());
+        let full_trace = TreeVec::new(
+            trace
+                .0
+                .into_iter()
+                .zip(other_trace.0)
+                .enumerate()
+                .map(|(tree_index, (columns, other_columns))| match tree_index {
+                    0 => columns,
+                    _ => other_columns.into_iter().chain(columns).collect(),
+                })
+                .collect(),
+        );
+
+        component
+            .assert_constraints("counter", &full_trace)
+            .unwrap();
+    }
+
+    /// [CounterEval] with the logup in the second interaction phase, but declaring only one phase.
+    struct UndeclaredPhaseEval(CounterEval);
+
+    impl FrameworkEval for UndeclaredPhaseEval {
+        fn log_size(&self) -> u32 {
+            self.0.log_size()
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            self.0.max_constraint_log_degree_bound()
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, eval: E) -> E {
+            self.0.evaluate(eval)
+        }
+    }
+
+    fn second_phase_counter_eval() -> CounterEval {
+        CounterEval {
+            logup_interaction: interaction_trace_idx(1),
+            ..counter_eval()
+        }
+    }
+
+    #[test]
+    fn test_logup_in_second_interaction_phase() {
+        let eval = second_phase_counter_eval();
+        let (mut trace, claimed_sum) = counter_trace(&eval, BaseField::from(5));
+        // The first interaction phase has no columns of this component.
+        trace.insert(INTERACTION_TRACE_IDX, vec![]);
+        assert_constraints("counter", &eval, &trace.as_cols_ref(), claimed_sum).unwrap();
+
+        let component =
+            FrameworkComponent::new(&mut TraceLocationAllocator::default(), eval, claimed_sum);
+        assert_eq!(
+            component
+                .trace_log_degree_bounds()
+                .iter()
+                .map(|tree| tree.len())
+                .collect_vec(),
+            [1, 1, 0, 4]
+        );
+        assert_eq!(component.trace_locations()[3].col_end, 4);
+        component.assert_constraints("counter", &trace).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "declares 1 interaction phases")]
+    fn test_undeclared_interaction_phase() {
+        FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            UndeclaredPhaseEval(second_phase_counter_eval()),
+            SecureField::zero(),
+        );
+    }
+
+    #[test]
+    fn test_counter_constraint_degrees() {
+        let eval = counter_eval().evaluate(DegreeEvaluator::new(LOG_SIZE));
+
+        // The counter constraint, then the logup batch: the cumulative sum times the denominator.
+        assert_eq!(eval.constraint_degrees, [2, 2]);
+        assert_eq!(eval.max_constraint_log_degree_bound(), LOG_SIZE + 1);
+    }
+
+    /// How [BatchedCounterEval] batches its logup fractions.
+    #[derive(Clone)]
+    enum LogupBatching {
+        Manual(Vec<usize>),
+        ByDegree(usize),
+    }
+
+    /// A counter that yields `cur`, `cur + 1`, `cur^2` and `cur + 2` to a relation, whose
+    /// denominators have degrees 1, 1, 2 and 1.
+    struct BatchedCounterEval {
+        counter: CounterEval,
+        batching: LogupBatching,
+    }
+
+    impl FrameworkEval for BatchedCounterEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let is_first = eval.get_preprocessed_column(PreProcessedColumnId {
+                id: "is_first".into(),
+            });
+            let [prev, cur] = eval.next_interaction_mask(1, [-1, 0]);
+            eval.add_constraint((E::F::one() - is_first) * (cur.clone() - prev - E::F::one()));
+            let relation = &self.counter.relation;
+            for value in batched_counter_values(cur) {
+                eval.add_to_relation(RelationEntry::new(relation, E::EF::one(), &[value]));
+            }
+            match &self.batching {
+                LogupBatching::Manual(batching) => eval.finalize_logup_batched(batching),
+                LogupBatching::ByDegree(max_degree) => eval.finalize_logup_by_degree(*max_degree),
+            }
+            eval
+        }
+    }
+
+    fn batched_counter_values<F>(cur: F) -> [F; 4]
+    where
+        F: Clone + Add<Output = F> + Mul<Output = F> + From<BaseField>,
+    {
+        [
+            cur.clone(),
+            cur.clone() + F::from(BaseField::one()),
+            cur.clone() * cur.clone(),
+            cur + F::from(BaseField::from(2)),
+        ]
+    }
+
+    fn batched_counter_eval(batching: LogupBatching) -> BatchedCounterEval {
+        BatchedCounterEval {
+            counter: counter_eval(),
+            batching,
+        }
+    }
+
+    /// Returns the trace of [BatchedCounterEval] starting at `start` with the logup fractions
+    /// batched by `batching`, and its claimed sum.
+    fn batched_counter_trace(
+        eval: &BatchedCounterEval,
+        start: BaseField,
+        batching: &[usize],
+    ) -> (TreeVec<Vec<Vec<BaseField>>>, SecureField) {
+        let n_rows = 1 << LOG_SIZE;
+        let n_batches = batching.iter().max().unwrap() + 1;
+        let is_first = (0..n_rows)
+            .map(|row| BaseField::from((row == 0) as u32))
+            .collect_vec();
+        let counter = (0..n_rows)
+            .map(|row| start + BaseField::from(row))
+            .collect_vec();
+        let batch_sums = counter
+            .iter()
+            .map(|value| {
+                let mut sums = vec![SecureField::zero(); n_batches];
+                for (batch, value) in batching.iter().zip(batched_counter_values(*value)) {
+                    let denominator: SecureField = eval.counter.relation.combine(&[value]);
+                    sums[*batch] += denominator.inverse();
+                }
+                sums
+            })
+            .collect_vec();
+        let claimed_sum = batch_sums.iter().flatten().copied().sum::<SecureField>();
+        let cumsum_shift = claimed_sum / BaseField::from(n_rows);
+
+        // Every batch but the last adds a column of the sum of the batches so far in the row, the
+        // last column sums them over the rows.
+        let mut interaction = vec![vec![]; 4 * n_batches];
+        let mut cumsum = SecureField::zero();
+        for sums in batch_sums {
+            let mut row_sum = SecureField::zero();
+            for (batch, sum) in sums.iter().enumerate() {
+                row_sum += *sum;
+                let value = match batch == n_batches - 1 {
+                    true => {
+                        cumsum += row_sum - cumsum_shift;
+                        cumsum
+                    }
+                    false => row_sum,
+                };
+                for (i, coordinate) in value.to_m31_array().into_iter().enumerate() {
+                    interaction[4 * batch + i].push(coordinate);
+                }
+            }
+        }
+
+        (
+            TreeVec::new(vec![vec![is_first], vec![counter], interaction]),
+            claimed_sum,
+        )
+    }
+
+    #[test]
+    fn test_logup_batching_by_degree() {
+        for (max_degree, expected) in [(3, vec![1, 1, 0, 2]), (4, vec![0, 1, 0, 1])] {
+            let eval = batched_counter_eval(LogupBatching::ByDegree(max_degree));
+
+            let degrees = eval.evaluate(DegreeEvaluator::new(LOG_SIZE));
+            assert_eq!(degrees.logup.batching.as_ref(), Some(&expected));
+            assert!(degrees.constraint_degrees.iter().all(|d| *d <= max_degree));
+            let exprs = eval.evaluate(ExprEvaluator::new(LOG_SIZE, SecureField::zero()));
+            assert_eq!(exprs.logup.batching.as_ref(), Some(&expected));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Logup fraction 2 requires a constraint of degree 3")]
+    fn test_logup_batching_by_degree_rejects_low_maximum() {
+        let fractions = [1, 1, 2, 1].map(|degree| Fraction::new(Degree(0), Degree(degree)));
+
+        batch_by_degree(&fractions, 2);
+    }
+
+    #[test]
+    fn test_logup_batching_by_degree_matches_manual_batching() {
+        let batching = vec![1, 1, 0, 2];
+        let manual = batched_counter_eval(LogupBatching::Manual(batching.clone()));
+        let by_degree = batched_counter_eval(LogupBatching::ByDegree(3));
+        let (trace, claimed_sum) = batched_counter_trace(&manual, BaseField::from(5), &batching);
+
+        assert_constraints("manual", &manual, &trace.as_cols_ref(), claimed_sum).unwrap();
+        assert_constraints("by_degree", &by_degree, &trace.as_cols_ref(), claimed_sum).unwrap();
+
+        let manual =
+            FrameworkComponent::new(&mut TraceLocationAllocator::default(), manual, claimed_sum);
+        let by_degree = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            by_degree,
+            claimed_sum,
+        );
+        assert_eq!(manual.logup_batching(), None);
+        assert_eq!(by_degree.logup_batching(), Some(&batching));
+        assert_eq!(
+            by_degree.trace_log_degree_bounds().0,
+            manual.trace_log_degree_bounds().0
+        );
+        assert_eq!(
+            by_degree.max_constraint_log_degree_bound(),
+            manual.max_constraint_log_degree_bound()
+        );
+        by_degree.assert_constraints("by_degree", &trace).unwrap();
+
+        // The verifier evaluates the same quotients at any mask.
+        let point = SECURE_FIELD_CIRCLE_GEN.mul(1 << 20);
+        let mut value = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let mut mask: TreeVec<Vec<Vec<SecureField>>> = TreeVec::new(
+            manual
+                .mask_points(point)
+                .iter()
+                .map(|tree| {
+                    tree.iter()
+                        .map(|points| {
+                            points
+                                .iter()
+                                .map(|_| {
+                                    value = value.square() + SecureField::one();
+                                    value
+                                })
+                                .collect()
+                        })
+                        .collect()
+                })
+                .collect(),
+        );
+        mask[PREPROCESSED_TRACE_IDX] = vec![vec![value.inverse()]];
+        let random_coeff = SecureField::from_u32_unchecked(5, 6, 7, 8);
+        let mut manual_accumulator = PointEvaluationAccumulator::new(random_coeff);
+        let mut by_degree_accumulator = PointEvaluationAccumulator::new(random_coeff);
+        manual.evaluate_constraint_quotients_at_point(point, &mask, &mut manual_accumulator);
+        by_degree.evaluate_constraint_quotients_at_point(point, &mask, &mut by_degree_accumulator);
+        assert_eq!(
+            by_degree_accumulator.finalize(),
+            manual_accumulator.finalize()
+        );
+    }
+}
@@ -11,7 +11,10 @@ use itertools::Itertools;
 
 // use super::cpu_domain::CpuDomainEvaluator;
 use super::preprocessed_columns::PreProcessedColumnId;
-use super::{EvalAtRow, InfoEvaluator, PointEvaluator, PREPROCESSED_TRACE_IDX};
+use super::{
+    assert_constraints, interaction_trace_idx, Batching, ConstraintFailure, DegreeEvaluator,
+    EvalAtRow, InfoEvaluator, PointEvaluator, PREPROCESSED_TRACE_IDX,
+};
 use crate::air::accumulation::PointEvaluationAccumulator;
 use crate::air::Component;
 // use crate::backend::cpu::bit_reverse;
@@ -20,11 +23,9 @@ use crate::air::Component;
 // use crate::backend::simd::very_packed_m31::{VeryPackedBaseField, LOG_N_VERY_PACKED_ELEMS};
 // use crate::backend::simd::SimdBackend;
 use crate::circle::CirclePoint;
-use crate::constraints::coset_vanishing;
-// use crate::fields::m31::BaseField;
+use crate::fields::m31::BaseField;
 use crate::fields::qm31::SecureField;
 // use crate::fields::secure_column::SecureColumnByCoords;
-use crate::fields::FieldExpOps;
 use crate::pcs::{TreeSubspan, TreeVec};
 use crate::poly::circle::CanonicCoset;
 use crate::utils::all_unique;
@@ -108,12 +109,34 @@ impl TraceLocationAllocator {
 /// A component defined solely in means of the constraints framework.
 ///
 /// Implementing this trait introduces implementations for [`Component`] and [`ComponentProver`] for
-/// the SIMD backend. Note that the constraint framework only supports components with columns of
-/// the same size.
+/// the SIMD backend.
+///
+/// Columns may have different sizes, see [FrameworkEval::column_log_size]. Constraints on smaller
+/// columns are restricted to their domain with [EvalAtRow::set_constraint_domain].
 pub trait FrameworkEval {
+    /// The log size of the largest columns, which is the domain of the logup columns.
     fn log_size(&self) -> u32;
 
-    fn max_constraint_log_degree_bound(&self) -> u32;
+    /// Returns the log size of the `col_index`-th column of `interaction`, in the order the
+    /// columns are read. Preprocessed columns are indexed in the order they are requested.
+    /// Defaults to [FrameworkEval::log_size] for every column.
+    fn column_log_size(&self, _interaction: usize, _col_index: usize) -> u32 {
+        self.log_size()
+    }
+
+    /// Returns the number of interaction phases the component may read columns from, committed in
+    /// the trees following the original trace, see [interaction_trace_idx]. Defaults to one phase.
+    fn n_interaction_phases(&self) -> usize {
+        1
+    }
+
+    /// Returns the log size of the domain of the composition polynomial, which must fit the
+    /// quotients of all the constraints. Defaults to the smallest such bound, computed by
+    /// [DegreeEvaluator].
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.evaluate(DegreeEvaluator::new(self.log_size()))
+            .max_constraint_log_degree_bound()
+    }
 
     fn evaluate<E: EvalAtRow>(&self, eval: E) -> E;
 }
@@ -122,6 +145,9 @@ pub struct FrameworkComponent<C: FrameworkEval> {
     pub(super) eval: C,
     pub(super) trace_locations: TreeVec<TreeSubspan>,
     pub(super) preprocessed_column_indices: Vec<usize>,
+    column_log_sizes: TreeVec<ColumnVec<u32>>,
+    max_constraint_log_degree_bound: u32,
+    logup_batching: Option<Batching>,
     info: InfoEvaluator,
     claimed_sum: SecureField,
 }
@@ -132,7 +158,19 @@ impl<E: FrameworkEval> FrameworkComponent<E> {
         eval: E,
         claimed_sum: SecureField,
     ) -> Self {
-        let info = eval.evaluate(InfoEvaluator::new(eval.log_size(), vec![], claimed_sum));
+        // The degrees are known before the structure, which depends on the logup batching.
+        let degrees = eval.evaluate(DegreeEvaluator::new(eval.log_size()));
+        let logup_batching = degrees.logup.batching.clone();
+
+        let mut info = InfoEvaluator::new(eval.log_size(), vec![], claimed_sum);
+        info.logup.batching = logup_batching.clone();
+        let info = eval.evaluate(info);
+        assert!(
+            info.mask_offsets.len() <= interaction_trace_idx(eval.n_interaction_phases()),
+            "The component reads {} trees, but declares {} interaction phases",
+            info.mask_offsets.len(),
+            eval.n_interaction_phases()
+        );
         let trace_locations = location_allocator.next_for_structure(&info.mask_offsets);
 
         let preprocessed_column_indices = info
@@ -161,11 +199,52 @@ impl<E: FrameworkEval> FrameworkComponent<E> {
                 }
             })
             .collect();
+
+        let mut column_log_sizes: TreeVec<ColumnVec<u32>> = TreeVec::new(
+            info.mask_offsets
+                .iter()
+                .enumerate()
+                .map(|(interaction, tree_offsets)| {
+                    (0..tree_offsets.len())
+                        .map(|col_index| eval.column_log_size(interaction, col_index))
+                        .collect()
+                })
+                .collect(),
+        );
+        column_log_sizes[PREPROCESSED_TRACE_IDX] = (0..info.preprocessed_columns.len())
+            .map(|col_index| eval.column_log_size(PREPROCESSED_TRACE_IDX, col_index))
+            .collect();
+        assert!(
+            column_log_sizes
+                .iter()
+                .flatten()
+                .all(|log_size| *log_size <= eval.log_size()),
+            "Column log sizes must not exceed the component's log size"
+        );
+
+        // A bound that is too low would silently mis-commit the composition polynomial.
+        let max_constraint_log_degree_bound = eval.max_constraint_log_degree_bound();
+        if let Some((constraint_index, log_degree_bound)) = degrees
+            .constraint_log_degree_bounds
+            .iter()
+            .enumerate()
+            .find(|(_, log_degree_bound)| **log_degree_bound > max_constraint_log_degree_bound)
+        {
+            panic!(
+                "Constraint {constraint_index} of degree {} requires a log degree bound of \
+                 {log_degree_bound}, but the component declares {max_constraint_log_degree_bound}",
+                degrees.constraint_degrees[constraint_index]
+            );
+        }
+
         Self {
             eval,
             trace_locations,
             info,
             preprocessed_column_indices,
+            column_log_sizes,
+            max_constraint_log_degree_bound,
+            logup_batching,
             claimed_sum,
         }
     }
@@ -173,6 +252,44 @@ impl<E: FrameworkEval> FrameworkComponent<E> {
     pub fn trace_locations(&self) -> &[TreeSubspan] {
         &self.trace_locations
     }
+
+    /// Returns the batching of the logup fractions if the component finalizes its logup by
+    /// degree, see [EvalAtRow::finalize_logup_by_degree]. The interaction trace must be generated
+    /// with the same batching.
+    pub fn logup_batching(&self) -> Option<&Batching> {
+        self.logup_batching.as_ref()
+    }
+
+    /// Evaluates the constraints on every row of the component's columns in `trace`, and returns
+    /// all the failures, reported under the name `component`. See [assert_constraints].
+    ///
+    /// `trace` holds the columns of all components, as committed, but in natural order.
+    pub fn assert_constraints(
+        &self,
+        component: &str,
+        trace: &TreeVec<ColumnVec<Vec<BaseField>>>,
+    ) -> Result<(), Vec<ConstraintFailure>> {
+        assert_constraints(
+            component,
+            &self.eval,
+            &self.component_trace(trace),
+            self.claimed_sum,
+        )
+    }
+
+    /// Returns the columns of the component in the trace of all components.
+    pub(super) fn component_trace<'a, T>(
+        &self,
+        trace: &'a TreeVec<ColumnVec<T>>,
+    ) -> TreeVec<ColumnVec<&'a T>> {
+        let mut component_trace = trace.sub_tree(&self.trace_locations);
+        component_trace[PREPROCESSED_TRACE_IDX] = self
+            .preprocessed_column_indices
+            .iter()
+            .map(|idx| &trace[PREPROCESSED_TRACE_IDX][*idx])
+            .collect();
+        component_trace
+    }
 }
 
 impl<E: FrameworkEval> Component for FrameworkComponent<E> {
@@ -181,36 +298,33 @@ impl<E: FrameworkEval> Component for FrameworkComponent<E> {
     }
 
     fn max_constraint_log_degree_bound(&self) -> u32 {
-        self.eval.max_constraint_log_degree_bound()
+        self.max_constraint_log_degree_bound
     }
 
     fn trace_log_degree_bounds(&self) -> TreeVec<ColumnVec<u32>> {
-        let mut log_degree_bounds = self
-            .info
-            .mask_offsets
-            .as_ref()
-            .map(|tree_offsets| vec![self.eval.log_size(); tree_offsets.len()]);
-
-        log_degree_bounds[0] = self
-            .preprocessed_column_indices
-            .iter()
-            .map(|_| self.eval.log_size())
-            .collect();
-
-        log_degree_bounds
+        self.column_log_sizes.clone()
     }
 
     fn mask_points(
         &self,
         point: CirclePoint<SecureField>,
     ) -> TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>> {
-        let trace_step = CanonicCoset::new(self.eval.log_size()).step();
-        self.info.mask_offsets.as_ref().map_cols(|col_offsets| {
-            col_offsets
-                .iter()
-                .map(|offset| point + trace_step.mul_signed(*offset).into_ef())
-                .collect()
-        })
+        // Preprocessed columns have no mask offsets, their mask points are set by [Components].
+        self.info
+            .mask_offsets
+            .as_ref()
+            .zip_eq(self.column_log_sizes.as_ref())
+            .map(|(tree_offsets, tree_log_sizes)| {
+                zip(tree_offsets, tree_log_sizes)
+                    .map(|(col_offsets, log_size)| {
+                        let step = CanonicCoset::new(*log_size).step();
+                        col_offsets
+                            .iter()
+                            .map(|offset| point + step.mul_signed(*offset).into_ef())
+                            .collect()
+                    })
+                    .collect()
+            })
     }
 
     fn preproccessed_column_indices(&self) -> ColumnVec<usize> {
@@ -232,13 +346,15 @@ impl<E: FrameworkEval> Component for FrameworkComponent<E> {
         let mut mask_points = mask.sub_tree(&self.trace_locations);
         mask_points[PREPROCESSED_TRACE_IDX] = preprocessed_mask;
 
-        self.eval.evaluate(PointEvaluator::new(
+        let mut evaluator = PointEvaluator::new(
             mask_points,
             evaluation_accumulator,
-            coset_vanishing(CanonicCoset::new(self.eval.log_size()).coset, point).inverse(),
+            point,
             self.eval.log_size(),
             self.claimed_sum,
-        ));
+        );
+        evaluator.logup.batching = self.logup_batching.clone();
+        self.eval.evaluate(evaluator);
     }
 }
 
@@ -409,9 +525,12 @@ impl<E: FrameworkEval> Display for FrameworkComponent<E> {
         )?;
         writeln!(
             f,
-            "total felts: 2^{} * {}",
-            log_n_rows,
-            n_cols.iter().sum::<usize>()
+            "total felts: {}",
+            self.column_log_sizes
+                .iter()
+                .flatten()
+                .map(|log_size| 1_usize << log_size)
+                .sum::<usize>()
         )?;
         for (j, n_cols) in n_cols.into_iter().enumerate() {
             writeln!(f, "\t Interaction {}: n_cols {}", j, n_cols)?;
@@ -419,3 +538,544 @@ impl<E: FrameworkEval> Display for FrameworkComponent<E> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use itertools::Itertools;
+    use num_traits::{One, Zero};
+
+    use super::{FrameworkComponent, FrameworkEval, TraceLocationAllocator};
+    use crate::air::accumulation::PointEvaluationAccumulator;
+    use crate::air::Component;
+    use crate::backend::CpuBackend;
+    use crate::circle::{CirclePoint, Coset, SECURE_FIELD_CIRCLE_GEN};
+    use crate::constraint_framework::periodic_columns::PeriodicColumn;
+    use crate::constraint_framework::preprocessed_columns::PreProcessedColumnId;
+    use crate::constraint_framework::{ConstraintDomain, ConstraintFailure, EvalAtRow};
+    use crate::constraints::{coset_vanishing, point_vanishing};
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::pcs::TreeVec;
+    use crate::poly::circle::{CanonicCoset, CircleEvaluation};
+    use crate::poly::{BitReversedOrder, NaturalOrder};
+
+    const LOG_SIZE: u32 = 3;
+    const AUX_LOG_SIZE: u32 = 2;
+
+    /// A counter of size `2^LOG_SIZE` next to an alternating column of size `2^AUX_LOG_SIZE`.
+    struct MixedSizesEval;
+
+    impl FrameworkEval for MixedSizesEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn column_log_size(&self, interaction: usize, col_index: usize) -> u32 {
+            match (interaction, col_index) {
+                (1, 1) => AUX_LOG_SIZE,
+                _ => LOG_SIZE,
+            }
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            LOG_SIZE + 1
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let is_first = eval.get_preprocessed_column(PreProcessedColumnId {
+                id: "is_first".into(),
+            });
+            let [prev, cur] = eval.next_interaction_mask(1, [-1, 0]);
+            eval.add_constraint((E::F::one() - is_first) * (cur - prev - E::F::one()));
+
+            let [aux, aux_next] = eval.next_interaction_mask(1, [0, 1]);
+            eval.set_constraint_domain(AUX_LOG_SIZE);
+            eval.add_constraint(aux + aux_next - E::F::one());
+            eval
+        }
+    }
+
+    fn mixed_sizes_trace() -> TreeVec<Vec<Vec<BaseField>>> {
+        let column = |values: &[u32]| values.iter().copied().map(BaseField::from).collect_vec();
+        TreeVec::new(vec![
+            vec![column(&[1, 0, 0, 0, 0, 0, 0, 0])],
+            vec![column(&[5, 6, 7, 8, 9, 10, 11, 12]), column(&[0, 1, 0, 1])],
+        ])
+    }
+
+    fn point() -> CirclePoint<SecureField> {
+        SECURE_FIELD_CIRCLE_GEN.mul(1 << 20)
+    }
+
+    fn eval_column_at_point(values: &[BaseField], point: CirclePoint<SecureField>) -> SecureField {
+        let log_size = values.len().ilog2();
+        CircleEvaluation::<CpuBackend, BaseField, BitReversedOrder>::new_canonical_ordered(
+            CanonicCoset::new(log_size),
+            values.to_vec(),
+        )
+        .interpolate()
+        .eval_at_point(point)
+    }
+
+    /// Returns the constraint index and row of each failure, which must all be constraint failures.
+    fn failed_rows(failures: &[ConstraintFailure]) -> Vec<(usize, usize)> {
+        failures
+            .iter()
+            .map(|failure| match failure {
+                ConstraintFailure::Constraint {
+                    constraint_index,
+                    row,
+                    ..
+                } => (*constraint_index, *row),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    /// Returns whether the quotient of a constraint on `column` has degree less than
+    /// `2^log_degree_bound`. `quotient` maps the value of the column at a point to the quotient
+    /// there.
+    fn is_quotient_low_degree(
+        column: &[BaseField],
+        log_degree_bound: u32,
+        quotient: impl Fn(BaseField, CirclePoint<BaseField>) -> BaseField,
+    ) -> bool {
+        let poly =
+            CircleEvaluation::<CpuBackend, BaseField, BitReversedOrder>::new_canonical_ordered(
+                CanonicCoset::new(column.len().ilog2()),
+                column.to_vec(),
+            )
+            .interpolate();
+        let domain = CanonicCoset::new(log_degree_bound + 1).circle_domain();
+        let values = domain
+            .iter()
+            .map(|p| quotient(poly.eval_at_point(p.into_ef()).to_m31_array()[0], p))
+            .collect_vec();
+        CircleEvaluation::<CpuBackend, BaseField, NaturalOrder>::new(domain, values)
+            .bit_reverse()
+            .interpolate()
+            .coeffs[1 << log_degree_bound..]
+            .iter()
+            .all(|coeff| coeff.is_zero())
+    }
+
+    #[test]
+    fn test_mixed_sizes_structure() {
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            MixedSizesEval,
+            SecureField::zero(),
+        );
+        let main_step = CanonicCoset::new(LOG_SIZE).step().into_ef();
+        let aux_step = CanonicCoset::new(AUX_LOG_SIZE).step().into_ef();
+
+        assert_eq!(
+            component.trace_log_degree_bounds().0,
+            [vec![LOG_SIZE], vec![LOG_SIZE, AUX_LOG_SIZE]]
+        );
+        assert_eq!(
+            component.mask_points(point())[1],
+            [
+                vec![point() - main_step, point()],
+                vec![point(), point() + aux_step],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mixed_sizes_assert_constraints() {
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            MixedSizesEval,
+            SecureField::zero(),
+        );
+        let mut trace = mixed_sizes_trace();
+        component.assert_constraints("mixed", &trace).unwrap();
+
+        trace[1][1][2] = BaseField::from(3);
+        let failures = component.assert_constraints("mixed", &trace).unwrap_err();
+
+        // The alternating constraint is only checked on the rows of its domain.
+        assert_eq!(failed_rows(&failures), [(1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_mixed_sizes_quotients_at_point() {
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            MixedSizesEval,
+            SecureField::zero(),
+        );
+        let trace = mixed_sizes_trace();
+        let mask_points = component.mask_points(point());
+        let mask = TreeVec::new(vec![
+            vec![vec![eval_column_at_point(&trace[0][0], point())]],
+            mask_points[1]
+                .iter()
+                .zip(&trace[1])
+                .map(|(points, values)| {
+                    points
+                        .iter()
+                        .map(|p| eval_column_at_point(values, *p))
+                        .collect()
+                })
+                .collect(),
+        ]);
+        let random_coeff = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let mut accumulator = PointEvaluationAccumulator::new(random_coeff);
+
+        component.evaluate_constraint_quotients_at_point(point(), &mask, &mut accumulator);
+
+        let is_first = mask[0][0][0];
+        let [prev, cur] = mask[1][0][..].try_into().unwrap();
+        let [aux, aux_next] = mask[1][1][..].try_into().unwrap();
+        let main_quotient = (SecureField::one() - is_first) * (cur - prev - SecureField::one())
+            / coset_vanishing(CanonicCoset::new(LOG_SIZE).coset(), point());
+        let aux_quotient = (aux + aux_next - SecureField::one())
+            / coset_vanishing(CanonicCoset::new(AUX_LOG_SIZE).coset(), point());
+        assert_eq!(
+            accumulator.finalize(),
+            main_quotient * random_coeff + aux_quotient
+        );
+    }
+
+    /// A counter of size `2^LOG_SIZE` from `5` to `12`, with its ends pinned by boundary constraints.
+    struct BoundaryEval;
+
+    impl FrameworkEval for BoundaryEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            LOG_SIZE + 1
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let [cur] = eval.next_interaction_mask(1, [0]);
+            eval.add_boundary_constraint(0, cur.clone() - E::F::from(BaseField::from(5)));
+            eval.add_boundary_constraint(
+                (1 << LOG_SIZE) - 1,
+                cur - E::F::from(BaseField::from(12)),
+            );
+            eval
+        }
+    }
+
+    #[test]
+    fn test_boundary_assert_constraints() {
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            BoundaryEval,
+            SecureField::zero(),
+        );
+        let mut trace = mixed_sizes_trace();
+        trace[1].truncate(1);
+        component.assert_constraints("boundary", &trace).unwrap();
+
+        trace[1][0][0] = BaseField::from(6);
+        trace[1][0][4] = BaseField::from(6);
+        let failures = component
+            .assert_constraints("boundary", &trace)
+            .unwrap_err();
+
+        // Boundary constraints are only checked on their row.
+        assert_eq!(failed_rows(&failures), [(0, 0)]);
+    }
+
+    #[test]
+    fn test_boundary_quotients_at_point() {
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            BoundaryEval,
+            SecureField::zero(),
+        );
+        let trace = mixed_sizes_trace();
+        let cur = eval_column_at_point(&trace[1][0], point());
+        let mask = TreeVec::new(vec![vec![], vec![vec![cur]]]);
+        let random_coeff = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let mut accumulator = PointEvaluationAccumulator::new(random_coeff);
+
+        component.evaluate_constraint_quotients_at_point(point(), &mask, &mut accumulator);
+
+        let coset = CanonicCoset::new(LOG_SIZE);
+        let first_quotient = (cur - BaseField::from(5)) / point_vanishing(coset.at(0), point());
+        let last_quotient = (cur - BaseField::from(12)) / point_vanishing(coset.at(7), point());
+        assert_eq!(
+            accumulator.finalize(),
+            first_quotient * random_coeff + last_quotient
+        );
+    }
+
+    #[test]
+    fn test_boundary_quotient_is_low_degree() {
+        let trace = mixed_sizes_trace();
+        let row_point = CanonicCoset::new(LOG_SIZE).at(0);
+
+        // The quotient of a satisfied boundary constraint of degree 1 fits in twice the trace
+        // size, while a violated one has a pole at the row.
+        for (start, is_low_degree) in [(5, true), (6, false)] {
+            assert_eq!(
+                is_quotient_low_degree(&trace[1][0], LOG_SIZE + 1, |value, p| {
+                    (value - BaseField::from(start)) / point_vanishing(row_point, p)
+                }),
+                is_low_degree
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The boundary row is outside the constraint domain")]
+    fn test_boundary_row_out_of_domain() {
+        struct OutOfDomainEval;
+
+        impl FrameworkEval for OutOfDomainEval {
+            fn log_size(&self) -> u32 {
+                LOG_SIZE
+            }
+
+            fn max_constraint_log_degree_bound(&self) -> u32 {
+                LOG_SIZE + 1
+            }
+
+            fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+                let [cur] = eval.next_interaction_mask(1, [0]);
+                eval.add_boundary_constraint(1 << LOG_SIZE, cur);
+                eval
+            }
+        }
+
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            OutOfDomainEval,
+            SecureField::zero(),
+        );
+        let mut trace = mixed_sizes_trace();
+        trace[1].truncate(1);
+        component.assert_constraints("out_of_domain", &trace).ok();
+    }
+
+    /// Squares of round constants repeating every 4 rows, checked at the end of each round.
+    struct RoundsEval;
+
+    fn round_constants() -> PeriodicColumn {
+        PeriodicColumn::new([1, 2, 3, 4].map(BaseField::from).to_vec())
+    }
+
+    fn round_ends() -> ConstraintDomain {
+        ConstraintDomain::strided(LOG_SIZE, 2, 3)
+    }
+
+    impl FrameworkEval for RoundsEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            LOG_SIZE + 1
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let [x] = eval.next_interaction_mask(1, [0]);
+            let constant = eval.get_periodic_column(&round_constants());
+            eval.add_constraint(x.clone() - constant.clone() * constant);
+
+            eval.set_strided_constraint_domain(round_ends());
+            eval.add_constraint(x - E::F::from(BaseField::from(16)));
+            eval
+        }
+    }
+
+    fn rounds_trace() -> TreeVec<Vec<Vec<BaseField>>> {
+        let column = [1, 4, 9, 16, 1, 4, 9, 16].map(BaseField::from).to_vec();
+        TreeVec::new(vec![vec![], vec![column]])
+    }
+
+    #[test]
+    fn test_strided_constraint_domain_rows() {
+        let trace_coset = CanonicCoset::new(LOG_SIZE);
+
+        assert_eq!(
+            round_ends().coset().iter().collect_vec(),
+            [trace_coset.at(3), trace_coset.at(7)]
+        );
+        assert_eq!(
+            (0..1 << LOG_SIZE)
+                .filter(|row| round_ends().contains(*row))
+                .collect_vec(),
+            [3, 7]
+        );
+        assert_eq!(ConstraintDomain::new(LOG_SIZE).coset(), trace_coset.coset());
+    }
+
+    #[test]
+    #[should_panic(expected = "The stride must be shorter than the trace")]
+    fn test_strided_constraint_domain_single_row() {
+        ConstraintDomain::strided(LOG_SIZE, LOG_SIZE, 0);
+    }
+
+    #[test]
+    fn test_rounds_assert_constraints() {
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            RoundsEval,
+            SecureField::zero(),
+        );
+        let mut trace = rounds_trace();
+        component.assert_constraints("rounds", &trace).unwrap();
+
+        trace[1][0][7] = BaseField::from(17);
+        let failures = component.assert_constraints("rounds", &trace).unwrap_err();
+
+        assert_eq!(failed_rows(&failures), [(0, 7), (1, 7)]);
+    }
+
+    #[test]
+    fn test_rounds_quotients_at_point() {
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            RoundsEval,
+            SecureField::zero(),
+        );
+        let trace = rounds_trace();
+        let x = eval_column_at_point(&trace[1][0], point());
+        let mask = TreeVec::new(vec![vec![], vec![vec![x]]]);
+        let random_coeff = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let mut accumulator = PointEvaluationAccumulator::new(random_coeff);
+
+        component.evaluate_constraint_quotients_at_point(point(), &mask, &mut accumulator);
+
+        let constant = eval_column_at_point(&round_constants().gen_column(LOG_SIZE), point());
+        let trace_coset = CanonicCoset::new(LOG_SIZE);
+        let round_ends_coset = Coset::new(trace_coset.index_at(3), LOG_SIZE - 2);
+        let square_quotient =
+            (x - constant * constant) / coset_vanishing(trace_coset.coset(), point());
+        let end_quotient = (x - BaseField::from(16)) / coset_vanishing(round_ends_coset, point());
+        assert_eq!(
+            accumulator.finalize(),
+            square_quotient * random_coeff + end_quotient
+        );
+    }
+
+    #[test]
+    fn test_strided_quotient_is_low_degree() {
+        let trace = rounds_trace();
+
+        // The quotient of a satisfied constraint of degree 1 on the round ends fits in the trace
+        // size, while a violated one has poles at the round ends.
+        for (end, is_low_degree) in [(16, true), (15, false)] {
+            assert_eq!(
+                is_quotient_low_degree(&trace[1][0], LOG_SIZE, |value, p| {
+                    (value - BaseField::from(end)) / coset_vanishing(round_ends().coset(), p)
+                }),
+                is_low_degree
+            );
+        }
+    }
+
+    /// Constrains the cube of a column, leaving the log degree bound to be inferred.
+    struct CubeEval;
+
+    impl CubeEval {
+        fn evaluate_cube<E: EvalAtRow>(mut eval: E) -> E {
+            let [x, y] = eval.next_interaction_mask(1, [0, 1]);
+            eval.add_constraint(x.clone() - y.clone());
+            eval.add_constraint(x.clone() * x.clone() * x - y);
+            eval
+        }
+    }
+
+    impl FrameworkEval for CubeEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, eval: E) -> E {
+            Self::evaluate_cube(eval)
+        }
+    }
+
+    /// [CubeEval] with a declared log degree bound.
+    struct DeclaredCubeEval(u32);
+
+    impl FrameworkEval for DeclaredCubeEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            self.0
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, eval: E) -> E {
+            CubeEval::evaluate_cube(eval)
+        }
+    }
+
+    #[test]
+    fn test_inferred_constraint_log_degree_bound() {
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            CubeEval,
+            SecureField::zero(),
+        );
+
+        assert_eq!(component.max_constraint_log_degree_bound(), LOG_SIZE + 1);
+    }
+
+    #[test]
+    fn test_declared_constraint_log_degree_bound() {
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            DeclaredCubeEval(LOG_SIZE + 3),
+            SecureField::zero(),
+        );
+
+        assert_eq!(component.max_constraint_log_degree_bound(), LOG_SIZE + 3);
+    }
+
+    #[test]
+    fn test_cube_constraint_fits_twice_the_trace_size() {
+        // The quotient of a cube on every row has degree `2 * 2^LOG_SIZE`.
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            DeclaredCubeEval(LOG_SIZE + 1),
+            SecureField::zero(),
+        );
+
+        assert_eq!(component.max_constraint_log_degree_bound(), LOG_SIZE + 1);
+    }
+
+    #[test]
+    fn test_every_row_quotient_is_low_degree() {
+        let is_first = &mixed_sizes_trace()[0][0];
+        let trace_coset = CanonicCoset::new(LOG_SIZE).coset();
+        let square =
+            |value: BaseField, p| (value * value - value) / coset_vanishing(trace_coset, p);
+        let cube =
+            |value: BaseField, p| (value * value * value - value) / coset_vanishing(trace_coset, p);
+
+        // The quotients of satisfied constraints of degrees 2 and 3 on every row both need twice
+        // the trace size.
+        assert!(!is_quotient_low_degree(is_first, LOG_SIZE, square));
+        assert!(is_quotient_low_degree(is_first, LOG_SIZE + 1, square));
+        assert!(!is_quotient_low_degree(is_first, LOG_SIZE, cube));
+        assert!(is_quotient_low_degree(is_first, LOG_SIZE + 1, cube));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Constraint 1 of degree 3 requires a log degree bound of 4, but the component \
+                    declares 3"
+    )]
+    fn test_too_low_constraint_log_degree_bound() {
+        FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            DeclaredCubeEval(LOG_SIZE),
+            SecureField::zero(),
+        );
+    }
+}
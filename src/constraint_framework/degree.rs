@@ -0,0 +1,330 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub};
+
+use indexmap_nostd::IndexMap as HashMap;
+use num_traits::{One, Zero};
+
+use super::logup::{batch_by_degree, LogupAtRow};
+use super::periodic_columns::PeriodicColumn;
+use super::{Batching, ConstraintDomain, EvalAtRow, INTERACTION_TRACE_IDX};
+use crate::fields::m31::BaseField;
+use crate::fields::qm31::SecureField;
+use crate::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::fields::FieldExpOps;
+use crate::lookups::utils::Fraction;
+
+/// Computes the degree of every constraint, logup batches included, as a polynomial in the columns
+/// of its domain, and the log degree bound its quotient requires.
+pub struct DegreeEvaluator {
+    /// The degree of each constraint, in the order they are added.
+    pub constraint_degrees: Vec<usize>,
+    /// The smallest log degree bound of the composition polynomial each constraint allows.
+    pub constraint_log_degree_bounds: Vec<u32>,
+    pub logup: LogupAtRow<Self>,
+    constraint_domain: ConstraintDomain,
+}
+
+impl DegreeEvaluator {
+    pub fn new(log_size: u32) -> Self {
+        Self {
+            constraint_degrees: vec![],
+            constraint_log_degree_bounds: vec![],
+            logup: LogupAtRow::new(INTERACTION_TRACE_IDX, SecureField::zero(), log_size),
+            constraint_domain: ConstraintDomain::new(log_size),
+        }
+    }
+
+    /// Returns the smallest log degree bound that fits the quotients of all the constraints, or
+    /// the log size of the trace if there are none.
+    pub fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.constraint_log_degree_bounds
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(self.logup.log_size)
+    }
+
+    /// Records a constraint of degree `degree`, whose quotient requires `log_degree_bound`.
+    fn record_constraint(&mut self, degree: usize, log_degree_bound: u32) {
+        self.constraint_degrees.push(degree);
+        self.constraint_log_degree_bounds.push(log_degree_bound);
+    }
+}
+
+/// Returns the log degree bound the quotient of a constraint of degree `degree` on `domain`
+/// requires.
+pub(crate) fn constraint_log_degree_bound(degree: usize, domain: ConstraintDomain) -> u32 {
+    // A column of the domain has the degree of its vanishing polynomial, so the quotient of a
+    // constraint of degree `d` on every row has `d - 1` times that degree. A domain
+    // `2^ceil(log2(d - 1))` times larger only fits it for an odd `d`: its polynomials have a
+    // single term of the largest degree, with a `y` factor like an odd power of a column. The
+    // vanishing polynomial of a strided domain is smaller, leaving up to `d` times that degree.
+    let log_expansion = match domain.log_step == 0 && degree % 2 == 1 {
+        true => log2_ceil(degree - 1),
+        false => log2_ceil(degree),
+    };
+    domain.log_size + log_expansion
+}
+
+/// Returns the log degree bound the quotient of a boundary constraint of degree `degree` on a row
+/// of `domain` requires.
+pub(crate) fn boundary_constraint_log_degree_bound(degree: usize, domain: ConstraintDomain) -> u32 {
+    // The quotient by a single row keeps the degree of the constraint.
+    domain.log_size + log2_ceil(degree + 1)
+}
+
+/// Returns `ceil(log2(n))`, with `0` for `n = 0`.
+fn log2_ceil(n: usize) -> u32 {
+    n.next_power_of_two().ilog2()
+}
+
+impl EvalAtRow for DegreeEvaluator {
+    type F = Degree;
+    type EF = Degree;
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        _interaction: usize,
+        _offsets: [isize; N],
+    ) -> [Self::F; N] {
+        [Degree(1); N]
+    }
+
+    fn get_periodic_column(&mut self, _column: &PeriodicColumn) -> Self::F {
+        // A periodic column is a polynomial of the size of the trace, like its columns.
+        Degree(1)
+    }
+
+    fn add_constraint<G>(&mut self, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+        let degree = (Degree::one() * constraint).0;
+        self.record_constraint(
+            degree,
+            constraint_log_degree_bound(degree, self.constraint_domain),
+        );
+    }
+
+    fn add_boundary_constraint<G>(&mut self, _row: usize, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+        let degree = (Degree::one() * constraint).0;
+        self.record_constraint(
+            degree,
+            boundary_constraint_log_degree_bound(degree, self.constraint_domain),
+        );
+    }
+
+    fn set_strided_constraint_domain(&mut self, domain: ConstraintDomain) {
+        self.constraint_domain = domain;
+    }
+
+    fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
+        values
+            .into_iter()
+            .fold(Degree::zero(), |acc, value| acc + value)
+    }
+
+    fn logup_batching_by_degree(&self, max_degree: usize) -> Batching {
+        batch_by_degree(&self.logup.fracs, max_degree)
+    }
+
+    super::logup_proxy!();
+}
+
+/// The degree of a value as a polynomial in the columns. Base and extension field values share
+/// this representation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Degree(pub usize);
+
+impl Zero for Degree {
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        // A degree doesn't determine whether its value is zero.
+        panic!()
+    }
+}
+
+impl One for Degree {
+    fn one() -> Self {
+        Self(0)
+    }
+}
+
+impl Add for Degree {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.max(rhs.0))
+    }
+}
+
+impl Sub for Degree {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, rhs: Self) -> Self {
+        self + rhs
+    }
+}
+
+impl Mul for Degree {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Neg for Degree {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self
+    }
+}
+
+impl AddAssign for Degree {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl MulAssign for Degree {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs
+    }
+}
+
+impl AddAssign<BaseField> for Degree {
+    fn add_assign(&mut self, _rhs: BaseField) {}
+}
+
+impl Add<BaseField> for Degree {
+    type Output = Self;
+
+    fn add(self, _rhs: BaseField) -> Self {
+        self
+    }
+}
+
+impl Mul<BaseField> for Degree {
+    type Output = Self;
+
+    fn mul(self, _rhs: BaseField) -> Self {
+        self
+    }
+}
+
+impl Add<SecureField> for Degree {
+    type Output = Self;
+
+    fn add(self, _rhs: SecureField) -> Self {
+        self
+    }
+}
+
+impl Sub<SecureField> for Degree {
+    type Output = Self;
+
+    fn sub(self, _rhs: SecureField) -> Self {
+        self
+    }
+}
+
+impl Mul<SecureField> for Degree {
+    type Output = Self;
+
+    fn mul(self, _rhs: SecureField) -> Self {
+        self
+    }
+}
+
+impl From<BaseField> for Degree {
+    fn from(_value: BaseField) -> Self {
+        Self::zero()
+    }
+}
+
+impl From<SecureField> for Degree {
+    fn from(_value: SecureField) -> Self {
+        Self::zero()
+    }
+}
+
+impl FieldExpOps for Degree {
+    /// # Panics
+    ///
+    /// Panics if the value isn't constant, as its inverse isn't a polynomial.
+    fn inverse(&self) -> Self {
+        assert_eq!(
+            self.0, 0,
+            "Cannot bound the degree of the inverse of a column"
+        );
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::One;
+
+    use super::DegreeEvaluator;
+    use crate::constraint_framework::preprocessed_columns::PreProcessedColumnId;
+    use crate::constraint_framework::{ConstraintDomain, EvalAtRow, FrameworkEval};
+    use crate::fields::m31::BaseField;
+
+    struct DegreesEval;
+
+    impl FrameworkEval for DegreesEval {
+        fn log_size(&self) -> u32 {
+            4
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            7
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let is_first = eval.get_preprocessed_column(PreProcessedColumnId {
+                id: "is_first".into(),
+            });
+            let [x, y] = eval.next_interaction_mask(1, [0, 1]);
+            let x_cubed = eval.add_intermediate(x.clone() * x.clone() * x.clone());
+            eval.add_constraint(is_first * (x.clone() - E::F::one()));
+            eval.add_constraint(
+                x_cubed.clone() * y.clone() + y.clone() - E::F::from(BaseField::from(3)),
+            );
+            eval.add_boundary_constraint(0, x.clone() * x.clone());
+            eval.set_strided_constraint_domain(ConstraintDomain::strided(4, 2, 1));
+            eval.add_constraint(x.clone() * y.clone() * y.clone());
+            eval.set_constraint_domain(2);
+            eval.add_constraint(y.clone() - E::F::one());
+            eval
+        }
+    }
+
+    #[test]
+    fn test_degree_evaluator() {
+        let eval = DegreesEval.evaluate(DegreeEvaluator::new(4));
+
+        assert_eq!(eval.constraint_degrees, [2, 4, 2, 3, 1]);
+        assert_eq!(eval.constraint_log_degree_bounds, [5, 6, 6, 6, 2]);
+        assert_eq!(eval.max_constraint_log_degree_bound(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "inverse of a column")]
+    fn test_degree_evaluator_rejects_inverse_of_column() {
+        let mut eval = DegreeEvaluator::new(4);
+        let [x] = eval.next_interaction_mask(1, [0]);
+        eval.add_constraint(crate::fields::FieldExpOps::inverse(&x));
+    }
+}
@@ -0,0 +1,64 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+use super::{BaseExpr, ColumnExpr, ExtExpr};
+use crate::fields::m31::BaseField;
+use crate::fields::qm31::SecureField;
+
+/// Values of the columns and named values of expressions.
+#[derive(Clone, Debug, Default)]
+pub struct ExprVarAssignment {
+    pub columns: BTreeMap<ColumnExpr, BaseField>,
+    pub params: BTreeMap<String, BaseField>,
+    pub ext_params: BTreeMap<String, SecureField>,
+}
+
+impl BaseExpr {
+    /// Evaluates the expression.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a column or named value isn't assigned.
+    pub fn assign(&self, assignment: &ExprVarAssignment) -> BaseField {
+        match self {
+            Self::Col(col) => *assignment
+                .columns
+                .get(col)
+                .unwrap_or_else(|| panic!("Column {col} is not assigned")),
+            Self::Const(value) => *value,
+            Self::Param(name) => *assignment
+                .params
+                .get(name)
+                .unwrap_or_else(|| panic!("Parameter {name} is not assigned")),
+            Self::Add(a, b) => a.assign(assignment) + b.assign(assignment),
+            Self::Sub(a, b) => a.assign(assignment) - b.assign(assignment),
+            Self::Mul(a, b) => a.assign(assignment) * b.assign(assignment),
+            Self::Neg(a) => -a.assign(assignment),
+            Self::Inv(a) => a.assign(assignment).inverse(),
+        }
+    }
+}
+
+impl ExtExpr {
+    /// Evaluates the expression.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a column or named value isn't assigned.
+    pub fn assign(&self, assignment: &ExprVarAssignment) -> SecureField {
+        match self {
+            Self::SecureCol(coordinates) => {
+                SecureField::from_m31_array(coordinates.each_ref().map(|c| c.assign(assignment)))
+            }
+            Self::Const(value) => *value,
+            Self::Param(name) => *assignment
+                .ext_params
+                .get(name)
+                .unwrap_or_else(|| panic!("Parameter {name} is not assigned")),
+            Self::Add(a, b) => a.assign(assignment) + b.assign(assignment),
+            Self::Sub(a, b) => a.assign(assignment) - b.assign(assignment),
+            Self::Mul(a, b) => a.assign(assignment) * b.assign(assignment),
+            Self::Neg(a) => -a.assign(assignment),
+        }
+    }
+}
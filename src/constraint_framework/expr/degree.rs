@@ -0,0 +1,96 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+use super::{BaseExpr, ExtExpr};
+
+/// Degrees of named values, e.g. intermediates. Names that are missing are formal parameters of
+/// the AIR, which have degree 0.
+pub type NamedExprDegrees = BTreeMap<String, usize>;
+
+impl BaseExpr {
+    /// Returns an upper bound on the degree of the expression as a polynomial in the trace columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the expression inverts a non-constant expression.
+    pub fn degree_bound(&self, named_degrees: &NamedExprDegrees) -> usize {
+        match self {
+            Self::Col(_) => 1,
+            Self::Const(_) => 0,
+            Self::Param(name) => named_degrees.get(name).copied().unwrap_or(0),
+            Self::Add(a, b) | Self::Sub(a, b) => a
+                .degree_bound(named_degrees)
+                .max(b.degree_bound(named_degrees)),
+            Self::Mul(a, b) => a.degree_bound(named_degrees) + b.degree_bound(named_degrees),
+            Self::Neg(a) => a.degree_bound(named_degrees),
+            Self::Inv(a) => {
+                assert_eq!(
+                    a.degree_bound(named_degrees),
+                    0,
+                    "Cannot bound the degree of the inverse of {a}"
+                );
+                0
+            }
+        }
+    }
+}
+
+impl ExtExpr {
+    /// Returns an upper bound on the degree of the expression as a polynomial in the trace columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the expression inverts a non-constant expression.
+    pub fn degree_bound(&self, named_degrees: &NamedExprDegrees) -> usize {
+        match self {
+            Self::SecureCol(coordinates) => coordinates
+                .iter()
+                .map(|coordinate| coordinate.degree_bound(named_degrees))
+                .max()
+                .unwrap(),
+            Self::Const(_) => 0,
+            Self::Param(name) => named_degrees.get(name).copied().unwrap_or(0),
+            Self::Add(a, b) | Self::Sub(a, b) => a
+                .degree_bound(named_degrees)
+                .max(b.degree_bound(named_degrees)),
+            Self::Mul(a, b) => a.degree_bound(named_degrees) + b.degree_bound(named_degrees),
+            Self::Neg(a) => a.degree_bound(named_degrees),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::string::ToString;
+
+    use super::super::{BaseExpr, ColumnExpr, ExtExpr};
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::fields::FieldExpOps;
+
+    #[test]
+    fn test_degree_bound() {
+        let x = BaseExpr::Col(ColumnExpr::from((1, 0, 0)));
+        let y = BaseExpr::Col(ColumnExpr::from((1, 1, -1)));
+        let named_degrees = BTreeMap::from([("intermediate0".to_string(), 3)]);
+
+        let expr = x.clone() * y.clone() * x.clone() + y.clone() * BaseField::from(5);
+        assert_eq!(expr.degree_bound(&named_degrees), 3);
+        let expr = BaseExpr::param("intermediate0") * x.clone() - BaseExpr::param("n_rows");
+        assert_eq!(expr.degree_bound(&named_degrees), 4);
+        let expr = (BaseExpr::param("n_rows") + BaseField::from(1)).inverse() * y.clone();
+        assert_eq!(expr.degree_bound(&named_degrees), 1);
+
+        let expr = ExtExpr::from(x.clone()) * SecureField::from_u32_unchecked(1, 2, 3, 4)
+            - ExtExpr::from(y.square()) * ExtExpr::param("intermediate0");
+        assert_eq!(expr.degree_bound(&named_degrees), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot bound the degree of the inverse")]
+    fn test_degree_bound_of_inverse_panics() {
+        let x = BaseExpr::Col(ColumnExpr::from((1, 0, 0)));
+        x.inverse().degree_bound(&BTreeMap::new());
+    }
+}
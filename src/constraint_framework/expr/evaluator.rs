@@ -0,0 +1,430 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Mul;
+
+use indexmap_nostd::IndexMap as HashMap;
+use itertools::Itertools;
+
+use super::degree::NamedExprDegrees;
+use super::{BaseExpr, ColumnExpr, ExtExpr};
+use crate::constraint_framework::degree::{
+    boundary_constraint_log_degree_bound, constraint_log_degree_bound,
+};
+use crate::constraint_framework::logup::{batch_by_degree, LogupAtRow};
+use crate::constraint_framework::periodic_columns::PeriodicColumn;
+use crate::constraint_framework::preprocessed_columns::PreProcessedColumnId;
+use crate::constraint_framework::{
+    Batching, ConstraintDomain, Degree, EvalAtRow, INTERACTION_TRACE_IDX, PREPROCESSED_TRACE_IDX,
+};
+use crate::fields::qm31::SecureField;
+use crate::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::lookups::utils::Fraction;
+use crate::pcs::TreeVec;
+
+/// Evaluates constraints symbolically, recording them as [ExtExpr]s.
+///
+/// Columns are numbered by their order of appearance in each interaction. Intermediates are
+/// recorded under the names `intermediate{i}` and `ext_intermediate{i}`, and periodic columns
+/// under `periodic_column_{i}`, and referenced as parameters by the expressions that use them.
+pub struct ExprEvaluator {
+    /// The mask offsets of each column, per interaction.
+    pub mask_offsets: TreeVec<Vec<Vec<isize>>>,
+    pub preprocessed_columns: Vec<PreProcessedColumnId>,
+    pub periodic_columns: Vec<PeriodicColumn>,
+    pub constraints: Vec<ExtExpr>,
+    /// The row of each constraint that only holds on one row, by constraint index.
+    pub boundary_rows: BTreeMap<usize, usize>,
+    /// The rows of the domain each constraint holds on, by constraint index.
+    pub constraint_domains: Vec<ConstraintDomain>,
+    /// The domain of the next constraints.
+    constraint_domain: ConstraintDomain,
+    pub intermediates: Vec<(String, BaseExpr)>,
+    pub ext_intermediates: Vec<(String, ExtExpr)>,
+    /// The logup fractions are kept in `logup.fracs` after the logup is finalized.
+    pub logup: LogupAtRow<Self>,
+}
+
+impl ExprEvaluator {
+    pub fn new(log_size: u32, claimed_sum: SecureField) -> Self {
+        Self {
+            mask_offsets: TreeVec::default(),
+            preprocessed_columns: vec![],
+            periodic_columns: vec![],
+            constraints: vec![],
+            boundary_rows: BTreeMap::new(),
+            constraint_domains: vec![],
+            constraint_domain: ConstraintDomain::new(log_size),
+            intermediates: vec![],
+            ext_intermediates: vec![],
+            logup: LogupAtRow::new(INTERACTION_TRACE_IDX, claimed_sum, log_size),
+        }
+    }
+
+    fn next_column(&mut self, interaction: usize, offsets: &[isize]) -> usize {
+        if self.mask_offsets.len() <= interaction {
+            self.mask_offsets.resize(interaction + 1, vec![]);
+        }
+        let columns = &mut self.mask_offsets[interaction];
+        columns.push(offsets.to_vec());
+        columns.len() - 1
+    }
+
+    /// Returns the degrees of the periodic columns and intermediates, by name.
+    pub fn intermediate_degrees(&self) -> NamedExprDegrees {
+        // Periodic columns are polynomials of the size of the trace, like its columns.
+        let mut degrees: NamedExprDegrees = (0..self.periodic_columns.len())
+            .map(|i| (format!("periodic_column_{i}"), 1))
+            .collect();
+        for (name, expr) in &self.intermediates {
+            let degree = expr.degree_bound(&degrees);
+            degrees.insert(name.clone(), degree);
+        }
+        for (name, expr) in &self.ext_intermediates {
+            let degree = expr.degree_bound(&degrees);
+            degrees.insert(name.clone(), degree);
+        }
+        degrees
+    }
+
+    /// Returns an upper bound on the degree of each constraint, in the trace columns.
+    pub fn constraint_degree_bounds(&self) -> Vec<usize> {
+        let degrees = self.intermediate_degrees();
+        self.constraints
+            .iter()
+            .map(|constraint| constraint.degree_bound(&degrees))
+            .collect()
+    }
+
+    /// Returns the log degree bound the quotient of each constraint requires on its domain.
+    pub fn constraint_log_degree_bounds(&self) -> Vec<u32> {
+        let degrees = self.constraint_degree_bounds();
+        (0..self.constraints.len())
+            .map(|i| {
+                let (degree, domain) = (degrees[i], self.constraint_domains[i]);
+                match self.boundary_rows.contains_key(&i) {
+                    true => boundary_constraint_log_degree_bound(degree, domain),
+                    false => constraint_log_degree_bound(degree, domain),
+                }
+            })
+            .collect()
+    }
+
+    /// Formats the simplified intermediates, logup fractions and constraints, one `let` statement
+    /// each. Constraints that don't hold on every row of the trace are followed by a comment with
+    /// their rows.
+    pub fn format_constraints(&self) -> String {
+        let preprocessed_columns =
+            self.preprocessed_columns
+                .iter()
+                .enumerate()
+                .map(|(idx, column)| {
+                    let col = ColumnExpr::from((PREPROCESSED_TRACE_IDX, idx, 0));
+                    format!("// {col}: {}", column.id)
+                });
+        let periodic_columns = self.periodic_columns.iter().enumerate().map(|(i, column)| {
+            format!(
+                "// periodic_column_{i}: [{}]",
+                column.values().iter().join(", ")
+            )
+        });
+        let intermediates = self
+            .intermediates
+            .iter()
+            .map(|(name, expr)| format!("let {name} = {};", expr.simplify()));
+        let ext_intermediates = self
+            .ext_intermediates
+            .iter()
+            .map(|(name, expr)| format!("let {name} = {};", expr.simplify()));
+        let logup_fractions = self.logup.fracs.iter().enumerate().map(|(i, fraction)| {
+            format!(
+                "let logup_fraction_{i} = ({}) / ({});",
+                fraction.numerator.simplify(),
+                fraction.denominator.simplify()
+            )
+        });
+        let trace_domain = ConstraintDomain::new(self.logup.log_size);
+        let constraints = self.constraints.iter().enumerate().map(|(i, constraint)| {
+            let statement = format!("let constraint_{i} = {};", constraint.simplify());
+            let domain = self.constraint_domains[i];
+            match self.boundary_rows.get(&i) {
+                Some(row) if domain.log_size == trace_domain.log_size => {
+                    format!("{statement} // row {row}")
+                }
+                Some(row) => format!("{statement} // row {row} of 2^{}", domain.log_size),
+                None if domain == trace_domain => statement,
+                None => format!("{statement} // {}", format_domain(domain)),
+            }
+        });
+
+        preprocessed_columns
+            .chain(periodic_columns)
+            .chain(intermediates)
+            .chain(ext_intermediates)
+            .chain(logup_fractions)
+            .chain(constraints)
+            .join("\n\n")
+    }
+}
+
+/// Describes the rows of `domain`, e.g. `rows 3 mod 4 of 2^3`.
+fn format_domain(domain: ConstraintDomain) -> String {
+    match domain.log_step {
+        0 => format!("rows of 2^{}", domain.log_size),
+        log_step => format!(
+            "rows {} mod {} of 2^{}",
+            domain.offset,
+            1 << log_step,
+            domain.log_size
+        ),
+    }
+}
+
+impl EvalAtRow for ExprEvaluator {
+    type F = BaseExpr;
+    type EF = ExtExpr;
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        interaction: usize,
+        offsets: [isize; N],
+    ) -> [Self::F; N] {
+        assert!(
+            interaction != PREPROCESSED_TRACE_IDX,
+            "Preprocessed should be accesses with `get_preprocessed_column`",
+        );
+        let idx = self.next_column(interaction, &offsets);
+        offsets.map(|offset| BaseExpr::Col(ColumnExpr::from((interaction, idx, offset))))
+    }
+
+    fn get_preprocessed_column(&mut self, column: PreProcessedColumnId) -> Self::F {
+        let idx = self.next_column(PREPROCESSED_TRACE_IDX, &[0]);
+        self.preprocessed_columns.push(column);
+        BaseExpr::Col(ColumnExpr::from((PREPROCESSED_TRACE_IDX, idx, 0)))
+    }
+
+    fn get_periodic_column(&mut self, column: &PeriodicColumn) -> Self::F {
+        let name = format!("periodic_column_{}", self.periodic_columns.len());
+        self.periodic_columns.push(column.clone());
+        BaseExpr::Param(name)
+    }
+
+    fn add_constraint<G>(&mut self, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF> + From<G>,
+    {
+        self.constraints.push(constraint.into());
+        self.constraint_domains.push(self.constraint_domain);
+    }
+
+    fn add_boundary_constraint<G>(&mut self, row: usize, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF> + From<G>,
+    {
+        self.boundary_rows.insert(self.constraints.len(), row);
+        self.add_constraint(constraint);
+    }
+
+    fn set_strided_constraint_domain(&mut self, domain: ConstraintDomain) {
+        self.constraint_domain = domain;
+    }
+
+    fn add_intermediate(&mut self, val: Self::F) -> Self::F {
+        let name = format!("intermediate{}", self.intermediates.len());
+        self.intermediates.push((name.clone(), val));
+        BaseExpr::Param(name)
+    }
+
+    fn add_extension_intermediate(&mut self, val: Self::EF) -> Self::EF {
+        let name = format!("ext_intermediate{}", self.ext_intermediates.len());
+        self.ext_intermediates.push((name.clone(), val));
+        ExtExpr::Param(name)
+    }
+
+    fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
+        ExtExpr::SecureCol(values.map(Box::new))
+    }
+
+    fn logup_batching_by_degree(&self, max_degree: usize) -> Batching {
+        let degrees = self.intermediate_degrees();
+        let fractions = self
+            .logup
+            .fracs
+            .iter()
+            .map(|fraction| {
+                Fraction::new(
+                    Degree(fraction.numerator.degree_bound(&degrees)),
+                    Degree(fraction.denominator.degree_bound(&degrees)),
+                )
+            })
+            .collect::<Vec<_>>();
+        batch_by_degree(&fractions, max_degree)
+    }
+
+    super::super::logup_proxy!();
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use num_traits::One;
+
+    use super::ExprEvaluator;
+    use crate::constraint_framework::logup::LookupElements;
+    use crate::constraint_framework::preprocessed_columns::PreProcessedColumnId;
+    use crate::constraint_framework::{
+        ConstraintDomain, EvalAtRow, FrameworkEval, Relation, RelationEFTraitBound, RelationEntry,
+    };
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::fields::FieldExpOps;
+
+    struct TestRelation(LookupElements<2>);
+
+    impl<F: Clone, EF: RelationEFTraitBound<F>> Relation<F, EF> for TestRelation {
+        fn combine(&self, values: &[F]) -> EF {
+            self.0.combine(values)
+        }
+
+        fn get_name(&self) -> &str {
+            "TestRelation"
+        }
+
+        fn get_size(&self) -> usize {
+            2
+        }
+    }
+
+    struct TestEval {
+        relation: TestRelation,
+    }
+
+    impl FrameworkEval for TestEval {
+        fn log_size(&self) -> u32 {
+            4
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            6
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let is_first = eval.get_preprocessed_column(PreProcessedColumnId {
+                id: "is_first".into(),
+            });
+            let [x_prev, x] = eval.next_interaction_mask(1, [-1, 0]);
+            let y = eval.next_trace_mask();
+            let x_squared = eval.add_intermediate(x.clone().square());
+            eval.add_constraint(is_first.clone() * (x.clone() - E::F::one()));
+            eval.add_constraint(x_squared.clone() * y.clone() - x_prev);
+            eval.add_to_relation(RelationEntry::new(&self.relation, E::EF::one(), &[x, y]));
+            eval.finalize_logup();
+            eval
+        }
+    }
+
+    fn test_eval() -> TestEval {
+        TestEval {
+            relation: TestRelation(LookupElements {
+                z: SecureField::from_u32_unchecked(1, 2, 3, 4),
+                alpha: SecureField::from_u32_unchecked(5, 6, 7, 8),
+                alpha_powers: [
+                    SecureField::one(),
+                    SecureField::from_u32_unchecked(5, 6, 7, 8),
+                ],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_expr_evaluator_records_eval() {
+        let eval = test_eval().evaluate(ExprEvaluator::new(4, SecureField::from(16)));
+
+        assert_eq!(
+            eval.mask_offsets.iter().cloned().collect::<Vec<_>>(),
+            [
+                vec![vec![0]],
+                vec![vec![-1, 0], vec![0]],
+                vec![vec![-1, 0]; 4]
+            ]
+        );
+        assert_eq!(eval.intermediates.len(), 1);
+        assert_eq!(eval.logup.fracs.len(), 1);
+        assert_eq!(eval.constraint_degree_bounds(), [2, 3, 2]);
+    }
+
+    #[test]
+    fn test_format_constraints() {
+        let eval = test_eval().evaluate(ExprEvaluator::new(4, SecureField::from(16)));
+
+        assert_eq!(
+            eval.format_constraints(),
+            "// trace_0_column_0_offset_0: is_first\n\n\
+             let intermediate0 = trace_1_column_0_offset_0 * trace_1_column_0_offset_0;\n\n\
+             let logup_fraction_0 = (1) / (trace_1_column_0_offset_0 + qm31(5, 6, 7, 8) * \
+             trace_1_column_1_offset_0 - qm31(1, 2, 3, 4));\n\n\
+             let constraint_0 = trace_0_column_0_offset_0 * (trace_1_column_0_offset_0 - 1);\n\n\
+             let constraint_1 = intermediate0 * trace_1_column_1_offset_0 - \
+             trace_1_column_0_offset_neg_1;\n\n\
+             let constraint_2 = (secure_col(trace_2_column_0_offset_0, trace_2_column_1_offset_0, \
+             trace_2_column_2_offset_0, trace_2_column_3_offset_0) - \
+             secure_col(trace_2_column_0_offset_neg_1, trace_2_column_1_offset_neg_1, \
+             trace_2_column_2_offset_neg_1, trace_2_column_3_offset_neg_1) + 1) * \
+             (trace_1_column_0_offset_0 + qm31(5, 6, 7, 8) * trace_1_column_1_offset_0 - \
+             qm31(1, 2, 3, 4)) - 1;"
+        );
+    }
+
+    #[test]
+    fn test_format_boundary_constraints() {
+        let mut eval = ExprEvaluator::new(4, SecureField::from(16));
+        let [x] = eval.next_interaction_mask(1, [0]);
+        eval.add_constraint(x.clone() * x.clone());
+        eval.add_boundary_constraint(15, x - BaseField::from(3));
+
+        assert_eq!(eval.boundary_rows.get(&1), Some(&15));
+        assert_eq!(
+            eval.format_constraints(),
+            "let constraint_0 = trace_1_column_0_offset_0 * trace_1_column_0_offset_0;\n\n\
+             let constraint_1 = trace_1_column_0_offset_0 - 3; // row 15"
+        );
+    }
+
+    #[test]
+    fn test_format_constraint_domains() {
+        let mut eval = ExprEvaluator::new(4, SecureField::from(16));
+        let [x] = eval.next_interaction_mask(1, [0]);
+        eval.set_strided_constraint_domain(ConstraintDomain::strided(4, 2, 3));
+        eval.add_constraint(x.clone() * x.clone());
+        eval.set_constraint_domain(2);
+        eval.add_constraint(x.clone() * x.clone() * x.clone());
+        eval.add_boundary_constraint(1, x.clone());
+        eval.set_constraint_domain(4);
+        eval.add_constraint(x.clone() * x);
+
+        assert_eq!(
+            eval.constraint_domains,
+            [
+                ConstraintDomain::strided(4, 2, 3),
+                ConstraintDomain::new(2),
+                ConstraintDomain::new(2),
+                ConstraintDomain::new(4),
+            ]
+        );
+        assert_eq!(eval.constraint_log_degree_bounds(), [5, 3, 3, 5]);
+        assert_eq!(
+            eval.format_constraints(),
+            "let constraint_0 = trace_1_column_0_offset_0 * trace_1_column_0_offset_0; // rows 3 \
+             mod 4 of 2^4\n\n\
+             let constraint_1 = trace_1_column_0_offset_0 * trace_1_column_0_offset_0 * \
+             trace_1_column_0_offset_0; // rows of 2^2\n\n\
+             let constraint_2 = trace_1_column_0_offset_0; // row 1 of 2^2\n\n\
+             let constraint_3 = trace_1_column_0_offset_0 * trace_1_column_0_offset_0;"
+        );
+    }
+}
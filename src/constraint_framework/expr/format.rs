@@ -0,0 +1,135 @@
+use core::fmt::{self, Display, Formatter};
+
+use num_traits::Zero;
+
+use super::{BaseExpr, ColumnExpr, ExtExpr};
+
+/// Binding strength of the outermost operation of an expression. Operands that bind weaker than
+/// their context requires are parenthesized.
+const ADD_PRECEDENCE: u8 = 1;
+const MUL_PRECEDENCE: u8 = 2;
+const NEG_PRECEDENCE: u8 = 3;
+const ATOM_PRECEDENCE: u8 = 4;
+
+impl Display for ColumnExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Self {
+            interaction,
+            idx,
+            offset,
+        } = self;
+        write!(f, "trace_{interaction}_column_{idx}_offset_")?;
+        match offset {
+            offset if *offset < 0 => write!(f, "neg_{}", offset.unsigned_abs()),
+            offset => write!(f, "{offset}"),
+        }
+    }
+}
+
+impl BaseExpr {
+    const fn precedence(&self) -> u8 {
+        match self {
+            Self::Add(..) | Self::Sub(..) => ADD_PRECEDENCE,
+            Self::Mul(..) | Self::Inv(_) => MUL_PRECEDENCE,
+            Self::Neg(_) => NEG_PRECEDENCE,
+            Self::Col(_) | Self::Const(_) | Self::Param(_) => ATOM_PRECEDENCE,
+        }
+    }
+
+    fn fmt_with_precedence(&self, f: &mut Formatter<'_>, min_precedence: u8) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            return write!(f, "({self})");
+        }
+        match self {
+            Self::Col(col) => write!(f, "{col}"),
+            Self::Const(value) => write!(f, "{value}"),
+            Self::Param(name) => write!(f, "{name}"),
+            Self::Add(a, b) => {
+                a.fmt_with_precedence(f, ADD_PRECEDENCE)?;
+                write!(f, " + ")?;
+                b.fmt_with_precedence(f, ADD_PRECEDENCE)
+            }
+            Self::Sub(a, b) => {
+                a.fmt_with_precedence(f, ADD_PRECEDENCE)?;
+                write!(f, " - ")?;
+                b.fmt_with_precedence(f, MUL_PRECEDENCE)
+            }
+            Self::Mul(a, b) => {
+                a.fmt_with_precedence(f, MUL_PRECEDENCE)?;
+                write!(f, " * ")?;
+                b.fmt_with_precedence(f, NEG_PRECEDENCE)
+            }
+            Self::Neg(a) => {
+                write!(f, "-")?;
+                a.fmt_with_precedence(f, NEG_PRECEDENCE)
+            }
+            Self::Inv(a) => {
+                write!(f, "1 / ")?;
+                a.fmt_with_precedence(f, NEG_PRECEDENCE)
+            }
+        }
+    }
+}
+
+impl Display for BaseExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_with_precedence(f, 0)
+    }
+}
+
+impl ExtExpr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Add(..) | Self::Sub(..) => ADD_PRECEDENCE,
+            Self::Mul(..) => MUL_PRECEDENCE,
+            Self::Neg(_) => NEG_PRECEDENCE,
+            Self::SecureCol(_) => self.as_base().map_or(ATOM_PRECEDENCE, BaseExpr::precedence),
+            Self::Const(_) | Self::Param(_) => ATOM_PRECEDENCE,
+        }
+    }
+
+    fn fmt_with_precedence(&self, f: &mut Formatter<'_>, min_precedence: u8) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            return write!(f, "({self})");
+        }
+        match self {
+            Self::SecureCol(coordinates) => match self.as_base() {
+                Some(value) => write!(f, "{value}"),
+                None => {
+                    let [a, b, c, d] = coordinates;
+                    write!(f, "secure_col({a}, {b}, {c}, {d})")
+                }
+            },
+            Self::Const(value) => match value.to_m31_array() {
+                [a, b, c, d] if b.is_zero() && c.is_zero() && d.is_zero() => write!(f, "{a}"),
+                [a, b, c, d] => write!(f, "qm31({a}, {b}, {c}, {d})"),
+            },
+            Self::Param(name) => write!(f, "{name}"),
+            Self::Add(a, b) => {
+                a.fmt_with_precedence(f, ADD_PRECEDENCE)?;
+                write!(f, " + ")?;
+                b.fmt_with_precedence(f, ADD_PRECEDENCE)
+            }
+            Self::Sub(a, b) => {
+                a.fmt_with_precedence(f, ADD_PRECEDENCE)?;
+                write!(f, " - ")?;
+                b.fmt_with_precedence(f, MUL_PRECEDENCE)
+            }
+            Self::Mul(a, b) => {
+                a.fmt_with_precedence(f, MUL_PRECEDENCE)?;
+                write!(f, " * ")?;
+                b.fmt_with_precedence(f, NEG_PRECEDENCE)
+            }
+            Self::Neg(a) => {
+                write!(f, "-")?;
+                a.fmt_with_precedence(f, NEG_PRECEDENCE)
+            }
+        }
+    }
+}
+
+impl Display for ExtExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_with_precedence(f, 0)
+    }
+}
@@ -0,0 +1,307 @@
+//! Symbolic expressions for constraints.
+//!
+//! [ExprEvaluator] evaluates a [FrameworkEval](super::FrameworkEval) over [BaseExpr] and [ExtExpr]
+//! values, recording its columns, intermediates, logup fractions and constraints as expression
+//! trees. These can then be formatted, simplified and have their degree bounded.
+
+mod assignment;
+mod degree;
+mod evaluator;
+mod format;
+mod simplify;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub};
+
+pub use assignment::ExprVarAssignment;
+pub use evaluator::ExprEvaluator;
+use num_traits::{One, Zero};
+
+use crate::fields::m31::BaseField;
+use crate::fields::qm31::SecureField;
+use crate::fields::FieldExpOps;
+
+/// A single base field column at index `idx` of interaction `interaction`, at mask offset `offset`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ColumnExpr {
+    pub interaction: usize,
+    pub idx: usize,
+    pub offset: isize,
+}
+
+impl From<(usize, usize, isize)> for ColumnExpr {
+    fn from((interaction, idx, offset): (usize, usize, isize)) -> Self {
+        Self {
+            interaction,
+            idx,
+            offset,
+        }
+    }
+}
+
+/// An expression over base field columns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BaseExpr {
+    Col(ColumnExpr),
+    Const(BaseField),
+    /// A named value, e.g. an intermediate or a formal parameter of the AIR.
+    Param(String),
+    Add(Box<BaseExpr>, Box<BaseExpr>),
+    Sub(Box<BaseExpr>, Box<BaseExpr>),
+    Mul(Box<BaseExpr>, Box<BaseExpr>),
+    Neg(Box<BaseExpr>),
+    Inv(Box<BaseExpr>),
+}
+
+/// An expression over the secure extension field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExtExpr {
+    /// A secure field value combined from its 4 base field coordinates, e.g. a secure column.
+    SecureCol([Box<BaseExpr>; 4]),
+    Const(SecureField),
+    /// A named value, e.g. an intermediate or a formal parameter of the AIR.
+    Param(String),
+    Add(Box<ExtExpr>, Box<ExtExpr>),
+    Sub(Box<ExtExpr>, Box<ExtExpr>),
+    Mul(Box<ExtExpr>, Box<ExtExpr>),
+    Neg(Box<ExtExpr>),
+}
+
+impl BaseExpr {
+    pub fn param(name: impl Into<String>) -> Self {
+        Self::Param(name.into())
+    }
+}
+
+impl ExtExpr {
+    pub fn param(name: impl Into<String>) -> Self {
+        Self::Param(name.into())
+    }
+
+    /// Returns the base field expression if this is a base field value embedded in the extension,
+    /// i.e. a [ExtExpr::SecureCol] whose last 3 coordinates are zero.
+    pub fn as_base(&self) -> Option<&BaseExpr> {
+        match self {
+            Self::SecureCol([value, rest @ ..]) if rest.iter().all(|c| c.is_zero()) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Zero for BaseExpr {
+    fn zero() -> Self {
+        Self::Const(BaseField::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+}
+
+impl One for BaseExpr {
+    fn one() -> Self {
+        Self::Const(BaseField::one())
+    }
+}
+
+impl Zero for ExtExpr {
+    fn zero() -> Self {
+        Self::Const(SecureField::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+}
+
+impl One for ExtExpr {
+    fn one() -> Self {
+        Self::Const(SecureField::one())
+    }
+}
+
+impl From<BaseField> for BaseExpr {
+    fn from(value: BaseField) -> Self {
+        Self::Const(value)
+    }
+}
+
+impl From<BaseField> for ExtExpr {
+    fn from(value: BaseField) -> Self {
+        Self::Const(value.into())
+    }
+}
+
+impl From<SecureField> for ExtExpr {
+    fn from(value: SecureField) -> Self {
+        Self::Const(value)
+    }
+}
+
+impl From<BaseExpr> for ExtExpr {
+    fn from(value: BaseExpr) -> Self {
+        Self::SecureCol([
+            Box::new(value),
+            Box::new(BaseExpr::zero()),
+            Box::new(BaseExpr::zero()),
+            Box::new(BaseExpr::zero()),
+        ])
+    }
+}
+
+/// Implements the binary operators of an expression type with itself and with the types that
+/// convert into it.
+macro_rules! impl_binary_ops {
+    ($expr:ty, $($rhs:ty),*) => {
+        impl Add for $expr {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self::Add(Box::new(self), Box::new(rhs))
+            }
+        }
+
+        impl Sub for $expr {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self::Sub(Box::new(self), Box::new(rhs))
+            }
+        }
+
+        impl Mul for $expr {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self {
+                Self::Mul(Box::new(self), Box::new(rhs))
+            }
+        }
+
+        impl Neg for $expr {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self::Neg(Box::new(self))
+            }
+        }
+
+        impl AddAssign for $expr {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = self.clone() + rhs
+            }
+        }
+
+        impl MulAssign for $expr {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = self.clone() * rhs
+            }
+        }
+
+        $(
+            impl Add<$rhs> for $expr {
+                type Output = Self;
+
+                fn add(self, rhs: $rhs) -> Self {
+                    self + Self::from(rhs)
+                }
+            }
+
+            impl Sub<$rhs> for $expr {
+                type Output = Self;
+
+                fn sub(self, rhs: $rhs) -> Self {
+                    self - Self::from(rhs)
+                }
+            }
+
+            impl Mul<$rhs> for $expr {
+                type Output = Self;
+
+                fn mul(self, rhs: $rhs) -> Self {
+                    self * Self::from(rhs)
+                }
+            }
+
+            impl AddAssign<$rhs> for $expr {
+                fn add_assign(&mut self, rhs: $rhs) {
+                    *self = self.clone() + rhs
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_ops!(BaseExpr, BaseField);
+impl_binary_ops!(ExtExpr, BaseField, SecureField, BaseExpr);
+
+impl Add<SecureField> for BaseExpr {
+    type Output = ExtExpr;
+
+    fn add(self, rhs: SecureField) -> ExtExpr {
+        ExtExpr::from(self) + rhs
+    }
+}
+
+impl Mul<SecureField> for BaseExpr {
+    type Output = ExtExpr;
+
+    fn mul(self, rhs: SecureField) -> ExtExpr {
+        ExtExpr::from(self) * rhs
+    }
+}
+
+impl FieldExpOps for BaseExpr {
+    fn inverse(&self) -> Self {
+        Self::Inv(Box::new(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use num_traits::One;
+
+    use super::{BaseExpr, ColumnExpr, ExtExpr};
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::fields::FieldExpOps;
+
+    fn col(idx: usize, offset: isize) -> BaseExpr {
+        BaseExpr::Col(ColumnExpr::from((1, idx, offset)))
+    }
+
+    #[test]
+    fn test_format_expr() {
+        let x = col(0, 0);
+        let y = col(1, -1);
+        let expr =
+            (x.clone() + y.clone()) * x.clone() - -(y.clone() - BaseField::from(3)) + x.inverse();
+
+        assert_eq!(
+            expr.to_string(),
+            "(trace_1_column_0_offset_0 + trace_1_column_1_offset_neg_1) * trace_1_column_0_offset_0 \
+             - -(trace_1_column_1_offset_neg_1 - 3) + 1 / trace_1_column_0_offset_0"
+        );
+    }
+
+    #[test]
+    fn test_format_ext_expr() {
+        let x = col(0, 1);
+        let secure_col = ExtExpr::SecureCol(
+            [col(2, 0), col(3, 0), col(4, 0), col(5, 0)].map(alloc::boxed::Box::new),
+        );
+        let expr = (x.clone() * SecureField::from_u32_unchecked(1, 2, 3, 4)) * secure_col
+            + ExtExpr::param("claimed_sum");
+
+        assert_eq!(
+            expr.to_string(),
+            "trace_1_column_0_offset_1 * qm31(1, 2, 3, 4) * secure_col(trace_1_column_2_offset_0, \
+             trace_1_column_3_offset_0, trace_1_column_4_offset_0, trace_1_column_5_offset_0) \
+             + claimed_sum"
+        );
+        assert_eq!(ExtExpr::from(x).as_base(), Some(&col(0, 1)));
+        assert_eq!(ExtExpr::one().as_base(), None);
+    }
+}
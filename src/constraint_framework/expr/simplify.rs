@@ -0,0 +1,213 @@
+use alloc::boxed::Box;
+
+use num_traits::{One, Zero};
+
+use super::{BaseExpr, ExtExpr};
+use crate::fields::m31::BaseField;
+use crate::fields::qm31::SecureField;
+use crate::fields::FieldExpOps;
+
+impl BaseExpr {
+    /// Returns an equivalent expression with constants folded and trivial operations removed.
+    pub fn simplify(&self) -> Self {
+        match self {
+            Self::Col(_) | Self::Const(_) | Self::Param(_) => self.clone(),
+            Self::Add(a, b) => match (a.simplify(), b.simplify()) {
+                (Self::Const(a), Self::Const(b)) => Self::Const(a + b),
+                (a, b) if a.is_zero() => b,
+                (a, b) if b.is_zero() => a,
+                (a, Self::Neg(b)) => Self::Sub(Box::new(a), b),
+                (a, b) => a + b,
+            },
+            Self::Sub(a, b) => match (a.simplify(), b.simplify()) {
+                (Self::Const(a), Self::Const(b)) => Self::Const(a - b),
+                (a, b) if a == b => Self::zero(),
+                (a, b) if a.is_zero() => (-b).simplify(),
+                (a, b) if b.is_zero() => a,
+                (a, Self::Neg(b)) => Self::Add(Box::new(a), b),
+                (a, b) => a - b,
+            },
+            Self::Mul(a, b) => match (a.simplify(), b.simplify()) {
+                (Self::Const(a), Self::Const(b)) => Self::Const(a * b),
+                (a, b) if a.is_zero() || b.is_zero() => Self::zero(),
+                (a, b) if a.is_one() => b,
+                (a, b) if b.is_one() => a,
+                (a, b) if a == Self::Const(-BaseField::one()) => (-b).simplify(),
+                (a, b) if b == Self::Const(-BaseField::one()) => (-a).simplify(),
+                (a, b) => a * b,
+            },
+            Self::Neg(a) => match a.simplify() {
+                Self::Const(a) => Self::Const(-a),
+                Self::Neg(a) => *a,
+                a => -a,
+            },
+            Self::Inv(a) => match a.simplify() {
+                Self::Const(a) if !a.is_zero() => Self::Const(a.inverse()),
+                Self::Inv(a) => *a,
+                a => a.inverse(),
+            },
+        }
+    }
+
+    /// Returns `true` if the expression is the constant one.
+    fn is_one(&self) -> bool {
+        *self == Self::one()
+    }
+}
+
+impl ExtExpr {
+    /// Returns an equivalent expression with constants folded and trivial operations removed.
+    pub fn simplify(&self) -> Self {
+        match self {
+            Self::SecureCol(coordinates) => {
+                let coordinates = coordinates.each_ref().map(|c| c.simplify());
+                match &coordinates {
+                    [BaseExpr::Const(a), BaseExpr::Const(b), BaseExpr::Const(c), BaseExpr::Const(d)] => {
+                        Self::Const(SecureField::from_m31_array([*a, *b, *c, *d]))
+                    }
+                    _ => Self::SecureCol(coordinates.map(Box::new)),
+                }
+            }
+            Self::Const(_) | Self::Param(_) => self.clone(),
+            Self::Add(a, b) => match (a.simplify(), b.simplify()) {
+                (Self::Const(a), Self::Const(b)) => Self::Const(a + b),
+                (a, b) if a.is_zero() => b,
+                (a, b) if b.is_zero() => a,
+                (a, Self::Neg(b)) => Self::Sub(Box::new(a), b),
+                (a, b) => a + b,
+            },
+            Self::Sub(a, b) => match (a.simplify(), b.simplify()) {
+                (Self::Const(a), Self::Const(b)) => Self::Const(a - b),
+                (a, b) if a == b => Self::zero(),
+                (a, b) if a.is_zero() => (-b).simplify(),
+                (a, b) if b.is_zero() => a,
+                (a, Self::Neg(b)) => Self::Add(Box::new(a), b),
+                (a, b) => a - b,
+            },
+            Self::Mul(a, b) => match (a.simplify(), b.simplify()) {
+                (Self::Const(a), Self::Const(b)) => Self::Const(a * b),
+                (a, b) if a.is_zero() || b.is_zero() => Self::zero(),
+                (a, b) if a.is_one() => b,
+                (a, b) if b.is_one() => a,
+                (a, b) if a == Self::Const(-SecureField::one()) => (-b).simplify(),
+                (a, b) if b == Self::Const(-SecureField::one()) => (-a).simplify(),
+                (a, b) => a * b,
+            },
+            Self::Neg(a) => match a.simplify() {
+                Self::Const(a) => Self::Const(-a),
+                Self::Neg(a) => *a,
+                a => -a,
+            },
+        }
+    }
+
+    /// Returns `true` if the expression is the constant one.
+    fn is_one(&self) -> bool {
+        *self == Self::one()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+
+    use num_traits::{One, Zero};
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::super::{BaseExpr, ColumnExpr, ExprVarAssignment, ExtExpr};
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::fields::FieldExpOps;
+
+    fn col(idx: usize) -> BaseExpr {
+        BaseExpr::Col(ColumnExpr::from((1, idx, 0)))
+    }
+
+    fn random_assignment(rng: &mut SmallRng) -> ExprVarAssignment {
+        let mut assignment = ExprVarAssignment::default();
+        for idx in 0..4 {
+            assignment.columns.insert(
+                ColumnExpr::from((1, idx, 0)),
+                BaseField::from(rng.gen::<u32>()),
+            );
+        }
+        assignment
+            .params
+            .insert("a".to_string(), BaseField::from(rng.gen::<u32>()));
+        assignment.ext_params.insert(
+            "b".to_string(),
+            SecureField::from_m31_array(core::array::from_fn(|_| {
+                BaseField::from(rng.gen::<u32>())
+            })),
+        );
+        assignment
+    }
+
+    #[test]
+    fn test_simplify_base_expr() {
+        let [x, y] = [col(0), col(1)];
+        let one = BaseExpr::one;
+        let zero = BaseExpr::zero;
+
+        assert_eq!(
+            (BaseExpr::from(BaseField::from(2)) * BaseField::from(3) + one()).simplify(),
+            BaseExpr::from(BaseField::from(7))
+        );
+        assert_eq!((x.clone() * one() + zero() * y.clone()).simplify(), x);
+        assert_eq!((x.clone() - -y.clone()).simplify(), x.clone() + y.clone());
+        assert_eq!((x.clone() + -y.clone()).simplify(), x.clone() - y.clone());
+        assert_eq!((zero() - -(-x.clone())).simplify(), -x.clone());
+        assert_eq!(
+            (x.clone() * y.clone() - x.clone() * y.clone()).simplify(),
+            zero()
+        );
+        assert_eq!((-one() * x.clone()).simplify(), -x.clone());
+        assert_eq!(x.inverse().inverse().simplify(), x);
+    }
+
+    #[test]
+    fn test_simplify_ext_expr() {
+        let x = col(0);
+        let secure_col = ExtExpr::SecureCol(
+            [1, 2, 3, 4].map(|i| Box::new(BaseExpr::from(BaseField::from(i)) * BaseExpr::one())),
+        );
+
+        assert_eq!(
+            secure_col.simplify(),
+            ExtExpr::Const(SecureField::from_u32_unchecked(1, 2, 3, 4))
+        );
+        assert_eq!(
+            (ExtExpr::from(x.clone() + BaseExpr::zero()) * ExtExpr::one()).simplify(),
+            ExtExpr::from(x)
+        );
+    }
+
+    #[test]
+    fn test_simplify_preserves_value() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let [x, y, z, w] = [col(0), col(1), col(2), col(3)];
+        let a = BaseExpr::param("a");
+        let b = ExtExpr::param("b");
+        let base_expr = (x.clone() * BaseExpr::one() - -(y.clone() + BaseExpr::zero()))
+            * (z.clone() - z.clone() + w.clone()).inverse()
+            + -(-a.clone() * BaseField::from(3))
+            - BaseExpr::zero() * x.clone();
+        let ext_expr = (ExtExpr::from(base_expr.clone()) + SecureField::zero()) * b.clone()
+            - -ExtExpr::SecureCol([x, y, z, a].map(Box::new)) * ExtExpr::one()
+            + b * SecureField::from_u32_unchecked(0, 0, 0, 0);
+
+        for _ in 0..10 {
+            let assignment = random_assignment(&mut rng);
+            assert_eq!(
+                base_expr.simplify().assign(&assignment),
+                base_expr.assign(&assignment)
+            );
+            assert_eq!(
+                ext_expr.simplify().assign(&assignment),
+                ext_expr.assign(&assignment)
+            );
+        }
+    }
+}
@@ -9,6 +9,7 @@ use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub};
 use num_traits::{One, Zero};
 
 use super::logup::LogupAtRow;
+use super::periodic_columns::PeriodicColumn;
 use super::preprocessed_columns::PreProcessedColumnId;
 use super::{EvalAtRow, INTERACTION_TRACE_IDX};
 use crate::constraint_framework::PREPROCESSED_TRACE_IDX;
@@ -78,6 +79,10 @@ impl EvalAtRow for InfoEvaluator {
         FieldCounter::one()
     }
 
+    fn get_periodic_column(&mut self, _column: &PeriodicColumn) -> Self::F {
+        FieldCounter::one()
+    }
+
     fn add_constraint<G>(&mut self, constraint: G)
     where
         Self::EF: Mul<G, Output = Self::EF>,
@@ -1,9 +1,10 @@
+use core::cmp::Reverse;
 use core::ops::{Mul, Sub};
 
 // use itertools::Itertools;
 use num_traits::{One, Zero};
 
-use super::EvalAtRow;
+use super::{Batching, Degree, EvalAtRow};
 use alloc::vec;
 use alloc::vec::Vec;
 // use crate::backend::simd::column::SecureColumn;
@@ -12,7 +13,7 @@ use alloc::vec::Vec;
 // use crate::backend::simd::qm31::PackedSecureField;
 // use crate::backend::simd::SimdBackend;
 // use crate::backend::Column;
-use crate::channel::Channel;
+use crate::channel::ChallengeSource;
 use crate::fields::m31::BaseField;
 use crate::fields::qm31::SecureField;
 // use crate::fields::secure_column::SecureColumnByCoords;
@@ -33,6 +34,8 @@ pub struct LogupAtRow<E: EvalAtRow> {
     pub fracs: Vec<Fraction<E::EF, E::EF>>,
     pub is_finalized: bool,
     pub log_size: u32,
+    /// The batching of [EvalAtRow::finalize_logup_by_degree], if given or already finalized.
+    pub batching: Option<Batching>,
 }
 
 impl<E: EvalAtRow> Default for LogupAtRow<E> {
@@ -48,6 +51,7 @@ impl<E: EvalAtRow> LogupAtRow<E> {
             fracs: vec![],
             is_finalized: true,
             log_size,
+            batching: None,
         }
     }
 
@@ -59,6 +63,7 @@ impl<E: EvalAtRow> LogupAtRow<E> {
             fracs: vec![],
             is_finalized: true,
             log_size: 10,
+            batching: None,
         }
     }
 }
@@ -71,6 +76,53 @@ impl<E: EvalAtRow> Drop for LogupAtRow<E> {
     }
 }
 
+/// Groups logup fractions, given by the degrees of their numerators and denominators, into batches
+/// whose constraints have degree at most `max_degree`, trying to use as few batches, i.e.
+/// interaction columns, as possible.
+///
+/// The fractions are placed by decreasing denominator degree, each into the first batch it fits
+/// in. The result only depends on the degrees, so the prover and the verifier agree on it.
+///
+/// # Panics
+///
+/// Panics if a single fraction requires a constraint of degree above `max_degree`.
+pub fn batch_by_degree(fractions: &[Fraction<Degree, Degree>], max_degree: usize) -> Batching {
+    let mut order: Vec<usize> = (0..fractions.len()).collect();
+    order.sort_by_key(|&i| Reverse(fractions[i].denominator));
+
+    let mut batches: Vec<Fraction<Degree, Degree>> = vec![];
+    let mut batching = vec![0; fractions.len()];
+    for i in order {
+        let fraction = fractions[i];
+        let batch = batches
+            .iter()
+            .position(|batch| batch_constraint_degree(*batch + fraction) <= max_degree);
+        batching[i] = match batch {
+            Some(batch) => {
+                batches[batch] = batches[batch] + fraction;
+                batch
+            }
+            None => {
+                let degree = batch_constraint_degree(fraction);
+                assert!(
+                    degree <= max_degree,
+                    "Logup fraction {i} requires a constraint of degree {degree}, above the \
+                     maximum of {max_degree}"
+                );
+                batches.push(fraction);
+                batches.len() - 1
+            }
+        };
+    }
+    batching
+}
+
+/// Returns the degree of the constraint `diff * denominator - numerator` of a batch whose
+/// fractions sum to `fraction`, where `diff` is a difference of cumulative sums.
+fn batch_constraint_degree(fraction: Fraction<Degree, Degree>) -> usize {
+    (Degree(1) * fraction.denominator - fraction.numerator).0
+}
+
 /// Interaction elements for the logup protocol.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct LookupElements<const N: usize> {
@@ -79,7 +131,7 @@ pub struct LookupElements<const N: usize> {
     pub alpha_powers: [SecureField; N],
 }
 impl<const N: usize> LookupElements<N> {
-    pub fn draw(channel: &mut impl Channel) -> Self {
+    pub fn draw(channel: &mut impl ChallengeSource) -> Self {
         let [z, alpha] = channel.draw_felts(2).try_into().unwrap();
         let mut cur = SecureField::one();
         let alpha_powers = core::array::from_fn(|_| {
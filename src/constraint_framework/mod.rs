@@ -1,13 +1,15 @@
 /// ! This module contains helpers to express and use constraints for components.
-// mod assert;
+mod assert;
 mod component;
 // mod cpu_domain;
-// pub mod expr;
+mod degree;
+pub mod expr;
 mod info;
 pub mod logup;
+pub mod periodic_columns;
 mod point;
 pub mod preprocessed_columns;
-// pub mod relation_tracker;
+pub mod relation_tracker;
 // mod simd_domain;
 
 use core::array;
@@ -15,30 +17,92 @@ use core::fmt::Debug;
 use core::ops::{Add, AddAssign, Mul, Neg, Sub};
 
 use alloc::vec::Vec;
-// pub use assert::{assert_constraints, AssertEvaluator};
+pub use assert::{assert_constraints, AssertEvaluator, ConstraintFailure};
 pub use component::{FrameworkComponent, FrameworkEval, TraceLocationAllocator};
+pub use degree::{Degree, DegreeEvaluator};
 pub use info::InfoEvaluator;
 use num_traits::{One, Zero};
+use periodic_columns::PeriodicColumn;
 pub use point::PointEvaluator;
 use preprocessed_columns::PreProcessedColumnId;
 // pub use simd_domain::SimdDomainEvaluator;
 
+use crate::circle::Coset;
 use crate::fields::m31::BaseField;
 use crate::fields::qm31::SecureField;
 use crate::fields::secure_column::SECURE_EXTENSION_DEGREE;
 use crate::fields::FieldExpOps;
 use crate::lookups::utils::Fraction;
+use crate::poly::circle::CanonicCoset;
 
 pub const PREPROCESSED_TRACE_IDX: usize = 0;
 pub const ORIGINAL_TRACE_IDX: usize = 1;
+/// The tree of the first interaction phase. Later phases, e.g. a second logup after more random
+/// challenges, are committed in the following trees, see [interaction_trace_idx].
 pub const INTERACTION_TRACE_IDX: usize = 2;
 
+/// Returns the index of the tree of the interaction phase `phase`, counting from 0.
+pub const fn interaction_trace_idx(phase: usize) -> usize {
+    INTERACTION_TRACE_IDX + phase
+}
+
 /// A vector that describes the batching of logup entries.
 /// Each vector member corresponds to a logup entry, and contains the batch number to which the
 /// entry should be added.
 /// Note that the batch numbers should be consecutive and start from 0, and that the vector's
 /// length should be equal to the number of logup entries.
-type Batching = Vec<usize>;
+pub type Batching = Vec<usize>;
+
+/// The rows on which constraints hold: every `2^log_step`-th row of a trace of size `2^log_size`,
+/// starting from row `offset`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstraintDomain {
+    pub log_size: u32,
+    pub log_step: u32,
+    pub offset: usize,
+}
+
+impl ConstraintDomain {
+    /// Every row of a trace of size `2^log_size`.
+    pub fn new(log_size: u32) -> Self {
+        Self {
+            log_size,
+            log_step: 0,
+            offset: 0,
+        }
+    }
+
+    /// A single row isn't a strided domain: it's constrained by
+    /// [EvalAtRow::add_boundary_constraint].
+    pub fn strided(log_size: u32, log_step: u32, offset: usize) -> Self {
+        assert!(
+            log_step < log_size,
+            "The stride must be shorter than the trace"
+        );
+        assert!(
+            offset < 1 << log_step,
+            "The offset must be smaller than the stride"
+        );
+        Self {
+            log_size,
+            log_step,
+            offset,
+        }
+    }
+
+    pub fn contains(&self, row: usize) -> bool {
+        row < 1 << self.log_size && row & ((1 << self.log_step) - 1) == self.offset
+    }
+
+    /// Returns the points of the rows of the domain. They form a sub-coset of the canonic coset of
+    /// the trace, whose step is the trace's step doubled `log_step` times.
+    pub fn coset(&self) -> Coset {
+        let trace_coset = CanonicCoset::new(self.log_size).coset();
+        Coset::subgroup(self.log_size)
+            .repeated_double(self.log_step)
+            .shift(trace_coset.index_at(self.offset))
+    }
+}
 
 /// A trait for evaluating expressions at some point or row.
 pub trait EvalAtRow {
@@ -93,6 +157,11 @@ pub trait EvalAtRow {
         mask_item
     }
 
+    /// Returns the value of a periodic column at the row. Its period repeats over the rows of the
+    /// trace, of size [FrameworkEval::log_size]. It isn't committed: the verifier evaluates it from
+    /// its period.
+    fn get_periodic_column(&mut self, column: &PeriodicColumn) -> Self::F;
+
     /// Returns the mask values of the given offsets for the next column in the interaction.
     fn next_interaction_mask<const N: usize>(
         &mut self,
@@ -119,6 +188,38 @@ pub trait EvalAtRow {
     where
         Self::EF: Mul<G, Output = Self::EF> + From<G>;
 
+    /// Adds a constraint that only holds on row `row` of the current constraint domain, e.g. an
+    /// initial or final state check.
+    ///
+    /// Its quotient is divided by a vanishing polynomial of the row's point only, so it keeps the
+    /// degree of the constraint: a boundary constraint of degree `d` requires
+    /// [FrameworkEval::max_constraint_log_degree_bound] to be at least
+    /// `log_size + ceil(log2(d + 1))`, against `log_size + ceil(log2(d))` on every row, or
+    /// `log_size + ceil(log2(d - 1))` for an odd `d > 1`.
+    /// Evaluators that don't tell rows apart check it like a constraint on every row by default.
+    fn add_boundary_constraint<G>(&mut self, _row: usize, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF> + From<G>,
+    {
+        self.add_constraint(constraint);
+    }
+
+    /// Sets the log size of the canonic coset on which the next constraints hold, until it's set
+    /// again. Constraints hold on the whole trace, of size [FrameworkEval::log_size], by default.
+    /// A constraint should only involve columns of the size of its domain.
+    fn set_constraint_domain(&mut self, log_size: u32) {
+        self.set_strided_constraint_domain(ConstraintDomain::new(log_size));
+    }
+
+    /// Sets the rows on which the next constraints hold to a strided domain, until it's set again,
+    /// e.g. the last row of every round of a permutation spread over several rows.
+    ///
+    /// The quotients are divided by a vanishing polynomial of the rows of the domain only: a
+    /// constraint of degree `d` on a domain with `log_step > 0` requires
+    /// [FrameworkEval::max_constraint_log_degree_bound] to be at least `log_size + ceil(log2(d))`.
+    /// Does nothing by default.
+    fn set_strided_constraint_domain(&mut self, _domain: ConstraintDomain) {}
+
     /// Adds an intermediate value in the base field to the component and returns its value.
     /// Does nothing by default.
     fn add_intermediate(&mut self, val: Self::F) -> Self::F {
@@ -149,6 +250,10 @@ pub trait EvalAtRow {
         self.write_logup_frac(frac);
     }
 
+    /// Sets the tree of the logup cumulative sum columns, [INTERACTION_TRACE_IDX] by default. Must
+    /// be called before the logup is finalized.
+    fn set_logup_interaction(&mut self, interaction: usize);
+
     // TODO(alont): Remove these once LogupAtRow is no longer used.
     fn write_logup_frac(&mut self, _fraction: Fraction<Self::EF, Self::EF>) {
         unimplemented!()
@@ -164,6 +269,20 @@ pub trait EvalAtRow {
     fn finalize_logup_in_pairs(&mut self) {
         unimplemented!();
     }
+
+    /// Finalizes the logup with as few batches as [logup::batch_by_degree] finds whose constraints
+    /// have degree at most `max_degree`. Evaluators that don't know the degrees of their values use
+    /// the batching given in their logup, which [FrameworkComponent] computes with a
+    /// [DegreeEvaluator].
+    fn finalize_logup_by_degree(&mut self, max_degree: usize);
+
+    /// Returns the batching of the logup fractions written so far by [logup::batch_by_degree].
+    fn logup_batching_by_degree(&self, _max_degree: usize) -> Batching {
+        panic!(
+            "The evaluator doesn't know the degrees of the logup fractions, its logup must be \
+             given the batching computed by a DegreeEvaluator"
+        );
+    }
 }
 
 /// Default implementation for evaluators that have an element called "logup" that works like a
@@ -171,6 +290,18 @@ pub trait EvalAtRow {
 /// TODO(alont): Remove once LogupAtRow is no longer used.
 macro_rules! logup_proxy {
     () => {
+        fn set_logup_interaction(&mut self, interaction: usize) {
+            assert!(
+                interaction >= crate::constraint_framework::INTERACTION_TRACE_IDX,
+                "The logup columns must be in an interaction tree"
+            );
+            assert!(
+                self.logup.fracs.is_empty(),
+                "The logup tree must be set before adding fractions"
+            );
+            self.logup.interaction = interaction;
+        }
+
         fn write_logup_frac(&mut self, fraction: Fraction<Self::EF, Self::EF>) {
             if self.logup.fracs.is_empty() {
                 self.logup.is_finalized = false;
@@ -183,6 +314,8 @@ macro_rules! logup_proxy {
         /// `batching` should contain the batch into which every logup entry should be inserted.
         fn finalize_logup_batched(&mut self, batching: &crate::constraint_framework::Batching) {
             assert!(!self.logup.is_finalized, "LogupAtRow was already finalized");
+            // The cumulative sum columns span the whole trace.
+            self.set_constraint_domain(self.logup.log_size);
             assert_eq!(
                 batching.len(),
                 self.logup.fracs.len(),
@@ -242,11 +375,22 @@ macro_rules! logup_proxy {
         }
 
         /// Finalizes the row's logup, batched in pairs.
-        /// TODO(alont) Remove this once a better batching mechanism is implemented.
+        /// TODO(alont) Remove this in favor of `finalize_logup_by_degree`.
         fn finalize_logup_in_pairs(&mut self) {
             let batches = (0..self.logup.fracs.len()).map(|n| n / 2).collect();
             self.finalize_logup_batched(&batches)
         }
+
+        /// Finalizes the row's logup batched by degree, with the batching given in `logup.batching`
+        /// if any, and records the batching used there.
+        fn finalize_logup_by_degree(&mut self, max_degree: usize) {
+            let batching = match self.logup.batching.clone() {
+                Some(batching) => batching,
+                None => self.logup_batching_by_degree(max_degree),
+            };
+            self.finalize_logup_batched(&batching);
+            self.logup.batching = Some(batching);
+        }
     };
 }
 pub(crate) use logup_proxy;
@@ -303,7 +447,7 @@ macro_rules! relation {
             pub fn dummy() -> Self {
                 Self($crate::constraint_framework::logup::LookupElements::dummy())
             }
-            pub fn draw(channel: &mut impl $crate::core::channel::Channel) -> Self {
+            pub fn draw(channel: &mut impl $crate::channel::ChallengeSource) -> Self {
                 Self($crate::constraint_framework::logup::LookupElements::draw(
                     channel,
                 ))
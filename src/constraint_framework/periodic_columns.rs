@@ -0,0 +1,116 @@
+//! Columns that repeat a short period of constant values, without being committed.
+//!
+//! Row `i` of a trace of size `2^log_size` is the `i`-th point of `CanonicCoset::new(log_size)`.
+//! Doubling that point `log_size - log_period` times gives the point of row `i % 2^log_period` of
+//! `CanonicCoset::new(log_period)`, so a periodic column is the interpolation of its period,
+//! composed with repeated doubling. The verifier evaluates it at any point from the period alone.
+
+use alloc::vec::Vec;
+
+use crate::backend::CpuBackend;
+use crate::circle::CirclePoint;
+use crate::fields::m31::BaseField;
+use crate::fields::qm31::SecureField;
+use crate::poly::circle::{CanonicCoset, CircleEvaluation};
+use crate::poly::BitReversedOrder;
+
+/// A column whose values repeat `values` over the rows of the trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeriodicColumn {
+    /// The values of one period, in natural order. Their number is a power of two.
+    values: Vec<BaseField>,
+}
+
+impl PeriodicColumn {
+    pub fn new(values: Vec<BaseField>) -> Self {
+        assert!(
+            values.len().is_power_of_two(),
+            "The period of a periodic column must be a power of two"
+        );
+        Self { values }
+    }
+
+    pub fn values(&self) -> &[BaseField] {
+        &self.values
+    }
+
+    pub fn log_period(&self) -> u32 {
+        self.values.len().ilog2()
+    }
+
+    /// Returns the value at row `row` of the trace.
+    pub fn at(&self, row: usize) -> BaseField {
+        self.values[row & (self.values.len() - 1)]
+    }
+
+    /// Generates the column over a trace of size `2^log_size`, in natural order.
+    pub fn gen_column(&self, log_size: u32) -> Vec<BaseField> {
+        (0..1 << log_size).map(|row| self.at(row)).collect()
+    }
+
+    /// Evaluates the column of a trace of size `2^log_size` at `point`.
+    pub fn eval_at_point(&self, log_size: u32, point: CirclePoint<SecureField>) -> SecureField {
+        let log_period = self.log_period();
+        assert!(
+            log_period <= log_size,
+            "The period is longer than the trace"
+        );
+        if log_period == 0 {
+            return self.values[0].into();
+        }
+        CircleEvaluation::<CpuBackend, BaseField, BitReversedOrder>::new_canonical_ordered(
+            CanonicCoset::new(log_period),
+            self.values.clone(),
+        )
+        .interpolate()
+        .eval_at_point(point.repeated_double(log_size - log_period))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::PeriodicColumn;
+    use crate::backend::CpuBackend;
+    use crate::circle::SECURE_FIELD_CIRCLE_GEN;
+    use crate::fields::m31::BaseField;
+    use crate::poly::circle::{CanonicCoset, CircleEvaluation};
+    use crate::poly::BitReversedOrder;
+
+    #[test]
+    fn test_periodic_column_gen_column() {
+        let column = PeriodicColumn::new([3, 1, 4, 1].map(BaseField::from).to_vec());
+
+        assert_eq!(column.log_period(), 2);
+        assert_eq!(
+            column.gen_column(3),
+            [3, 1, 4, 1, 3, 1, 4, 1].map(BaseField::from)
+        );
+    }
+
+    #[test]
+    fn test_periodic_column_eval_at_point() {
+        let log_size = 5;
+        let point = SECURE_FIELD_CIRCLE_GEN.mul(1 << 20);
+
+        for values in [vec![7], vec![2, 5], vec![3, 1, 4, 1, 5, 9, 2, 6]] {
+            let column = PeriodicColumn::new(values.into_iter().map(BaseField::from).collect());
+            let expected =
+                CircleEvaluation::<CpuBackend, BaseField, BitReversedOrder>::new_canonical_ordered(
+                    CanonicCoset::new(log_size),
+                    column.gen_column(log_size),
+                )
+                .interpolate()
+                .eval_at_point(point);
+
+            assert_eq!(column.eval_at_point(log_size, point), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn test_periodic_column_rejects_non_power_of_two_period() {
+        PeriodicColumn::new([1, 2, 3].map(BaseField::from).to_vec());
+    }
+}
@@ -1,10 +1,15 @@
 use super::logup::LogupAtRow;
-use super::{EvalAtRow, INTERACTION_TRACE_IDX};
+use super::periodic_columns::PeriodicColumn;
+use super::{ConstraintDomain, EvalAtRow, INTERACTION_TRACE_IDX};
 use crate::air::accumulation::PointEvaluationAccumulator;
+use crate::circle::CirclePoint;
+use crate::constraints::{coset_vanishing, point_vanishing};
 use crate::fields::qm31::SecureField;
 use crate::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::fields::FieldExpOps;
 use crate::lookups::utils::Fraction;
 use crate::pcs::TreeVec;
+use crate::poly::circle::CanonicCoset;
 use crate::ColumnVec;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -17,6 +22,10 @@ pub struct PointEvaluator<'a> {
     pub mask: TreeVec<ColumnVec<&'a Vec<SecureField>>>,
     pub evaluation_accumulator: &'a mut PointEvaluationAccumulator,
     pub col_index: Vec<usize>,
+    pub point: CirclePoint<SecureField>,
+    /// The rows on which the current constraints hold.
+    pub constraint_domain: ConstraintDomain,
+    /// The inverse of the vanishing polynomial of the current constraint domain at `point`.
     pub denom_inverse: SecureField,
     pub logup: LogupAtRow<Self>,
 }
@@ -24,7 +33,7 @@ impl<'a> PointEvaluator<'a> {
     pub fn new(
         mask: TreeVec<ColumnVec<&'a Vec<SecureField>>>,
         evaluation_accumulator: &'a mut PointEvaluationAccumulator,
-        denom_inverse: SecureField,
+        point: CirclePoint<SecureField>,
         log_size: u32,
         claimed_sum: SecureField,
     ) -> Self {
@@ -33,11 +42,18 @@ impl<'a> PointEvaluator<'a> {
             mask,
             evaluation_accumulator,
             col_index,
-            denom_inverse,
+            point,
+            constraint_domain: ConstraintDomain::new(log_size),
+            denom_inverse: domain_denom_inverse(ConstraintDomain::new(log_size), point),
             logup: LogupAtRow::new(INTERACTION_TRACE_IDX, claimed_sum, log_size),
         }
     }
 }
+
+/// Returns the inverse of the vanishing polynomial of the rows of `domain` at `point`.
+fn domain_denom_inverse(domain: ConstraintDomain, point: CirclePoint<SecureField>) -> SecureField {
+    coset_vanishing(domain.coset(), point).inverse()
+}
 impl EvalAtRow for PointEvaluator<'_> {
     type F = SecureField;
     type EF = SecureField;
@@ -53,6 +69,9 @@ impl EvalAtRow for PointEvaluator<'_> {
         assert_eq!(mask.len(), N);
         mask.try_into().unwrap()
     }
+    fn get_periodic_column(&mut self, column: &PeriodicColumn) -> Self::F {
+        column.eval_at_point(self.logup.log_size, self.point)
+    }
     fn add_constraint<G>(&mut self, constraint: G)
     where
         Self::EF: Mul<G, Output = Self::EF>,
@@ -60,6 +79,23 @@ impl EvalAtRow for PointEvaluator<'_> {
         self.evaluation_accumulator
             .accumulate(self.denom_inverse * constraint);
     }
+    fn add_boundary_constraint<G>(&mut self, row: usize, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+        assert!(
+            row < 1 << self.constraint_domain.log_size,
+            "The boundary row is outside the constraint domain"
+        );
+        let row_point = CanonicCoset::new(self.constraint_domain.log_size).at(row);
+        let denom_inverse = point_vanishing(row_point, self.point).inverse();
+        self.evaluation_accumulator
+            .accumulate(denom_inverse * constraint);
+    }
+    fn set_strided_constraint_domain(&mut self, domain: ConstraintDomain) {
+        self.constraint_domain = domain;
+        self.denom_inverse = domain_denom_inverse(domain, self.point);
+    }
     fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
         SecureField::from_partial_evals(values)
     }
@@ -1,17 +1,20 @@
-// use std::simd::Simd;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::zip;
 
-use alloc::string::String;
-// use num_traits::{One, Zero};
+use itertools::{izip, Itertools};
+use num_traits::{One, Zero};
 
-// use crate::backend::simd::m31::{PackedM31, N_LANES};
-// use crate::backend::simd::SimdBackend;
-// use crate::backend::{Col, Column};
-// use crate::backend::simd::m31::{PackedM31, N_LANES};
-// use crate::backend::simd::SimdBackend;
-
-// use crate::fields::m31::BaseField;
-// use crate::poly::circle::{CanonicCoset, CircleEvaluation};
-// use crate::poly::BitReversedOrder;
+use crate::backend::CpuBackend;
+use crate::circle::{CirclePoint, Coset};
+use crate::constraints::{coset_vanishing, point_vanishing};
+use crate::fields::m31::BaseField;
+use crate::fields::qm31::SecureField;
+use crate::poly::circle::{CanonicCoset, CircleEvaluation};
+use crate::poly::BitReversedOrder;
+use crate::{ColumnVec, VerificationError};
 
 /// Used for comparing preprocessed columns.
 /// Column IDs must be unique in a given context.
@@ -20,62 +23,400 @@ pub struct PreProcessedColumnId {
     pub id: String,
 }
 
-// /// A column with `1` at the first position, and `0` elsewhere.
-// #[derive(Debug, Clone)]
-// pub struct IsFirst {
-//     pub log_size: u32,
-// }
-// impl IsFirst {
-//     pub const fn new(log_size: u32) -> Self {
-//         Self { log_size }
-//     }
-
-//     pub fn packed_at(&self, vec_row: usize) -> PackedM31 {
-//         assert!(vec_row < (1 << self.log_size) / N_LANES);
-//         if vec_row == 0 {
-//             unsafe {
-//                 PackedM31::from_simd_unchecked(Simd::from_array(std::array::from_fn(|i| {
-//                     if i == 0 {
-//                         1
-//                     } else {
-//                         0
-//                     }
-//                 })))
-//             }
-//         } else {
-//             PackedM31::zero()
-//         }
-//     }
-
-//     // pub fn gen_column_simd(&self) -> CircleEvaluation<SimdBackend, BaseField, BitReversedOrder> {
-//     //     let mut col = Col::<SimdBackend, BaseField>::zeros(1 << self.log_size);
-//     //     col.set(0, BaseField::one());
-//     //     CircleEvaluation::new(CanonicCoset::new(self.log_size).circle_domain(), col)
-//     // }
-
-//     pub fn id(&self) -> PreProcessedColumnId {
-//         PreProcessedColumnId {
-//             id: format!("preprocessed_is_first_{}", self.log_size).to_string(),
-//         }
-//     }
-// }
-
-// #[cfg(test)]
-// mod tests {
-//     use super::IsFirst;
-//     use crate::core::backend::simd::m31::N_LANES;
-//     const LOG_SIZE: u32 = 8;
-
-//     #[test]
-//     fn test_packed_at_is_first() {
-//         let is_first = IsFirst::new(LOG_SIZE);
-//         let expected_is_first = is_first.gen_column_simd().to_cpu();
-
-//         for i in 0..(1 << LOG_SIZE) / N_LANES {
-//             assert_eq!(
-//                 is_first.packed_at(i).to_array(),
-//                 expected_is_first[i * N_LANES..(i + 1) * N_LANES]
-//             );
-//         }
-//     }
-// }
+/// A preprocessed column, i.e. a column that is fixed by the AIR and doesn't depend on the witness.
+///
+/// The prover commits to [PreProcessedColumn::gen_column]. The verifier can evaluate the column
+/// itself with [PreProcessedColumn::eval_at_point], and check the sampled values of the commitment
+/// against it, see [verify_preprocessed_columns].
+pub trait PreProcessedColumn {
+    fn log_size(&self) -> u32;
+
+    fn id(&self) -> PreProcessedColumnId;
+
+    /// Returns the values of the column in natural order, i.e. row `i` is the `i`-th point of the
+    /// column's canonic coset.
+    fn gen_column(&self) -> Vec<BaseField>;
+
+    /// Evaluates the polynomial of the column at `point`.
+    ///
+    /// The default implementation interpolates [PreProcessedColumn::gen_column], which takes
+    /// `O(n log n)` for a column of size `n`. Columns with a closed form should override it.
+    fn eval_at_point(&self, point: CirclePoint<SecureField>) -> SecureField {
+        interpolate_at_point(self, point)
+    }
+}
+
+/// Evaluates the polynomial of `column` at `point` by interpolating its values.
+fn interpolate_at_point<C: PreProcessedColumn + ?Sized>(
+    column: &C,
+    point: CirclePoint<SecureField>,
+) -> SecureField {
+    CircleEvaluation::<CpuBackend, BaseField, BitReversedOrder>::new_canonical_ordered(
+        CanonicCoset::new(column.log_size()),
+        column.gen_column(),
+    )
+    .interpolate()
+    .eval_at_point(point)
+}
+
+/// A column with `1` at the first row, and `0` elsewhere.
+#[derive(Debug, Clone)]
+pub struct IsFirst {
+    pub log_size: u32,
+}
+
+impl IsFirst {
+    pub const fn new(log_size: u32) -> Self {
+        Self { log_size }
+    }
+}
+
+impl PreProcessedColumn for IsFirst {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    fn id(&self) -> PreProcessedColumnId {
+        PreProcessedColumnId {
+            id: format!("preprocessed_is_first_{}", self.log_size),
+        }
+    }
+
+    fn gen_column(&self) -> Vec<BaseField> {
+        let mut column = vec![BaseField::zero(); 1 << self.log_size];
+        column[0] = BaseField::one();
+        column
+    }
+
+    fn eval_at_point(&self, point: CirclePoint<SecureField>) -> SecureField {
+        // The vanishing polynomial of the coset divided by the one of the first point vanishes on
+        // every row but the first, where it is `2^log_size`.
+        let coset = CanonicCoset::new(self.log_size).coset();
+        coset_vanishing(coset, point)
+            / (point_vanishing(coset.initial, point) * BaseField::from(1 << self.log_size))
+    }
+}
+
+/// A column with the row index at every row, i.e. `0, 1, 2, ...`.
+#[derive(Debug, Clone)]
+pub struct Seq {
+    pub log_size: u32,
+}
+
+impl Seq {
+    pub const fn new(log_size: u32) -> Self {
+        Self { log_size }
+    }
+}
+
+impl PreProcessedColumn for Seq {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    fn id(&self) -> PreProcessedColumnId {
+        PreProcessedColumnId {
+            id: format!("preprocessed_seq_{}", self.log_size),
+        }
+    }
+
+    fn gen_column(&self) -> Vec<BaseField> {
+        (0..1 << self.log_size).map(BaseField::from).collect()
+    }
+}
+
+/// A column with `1` at every `step`-th row, starting at the first, and `0` elsewhere.
+#[derive(Debug, Clone)]
+pub struct IsStep {
+    pub log_size: u32,
+    pub step: usize,
+}
+
+impl IsStep {
+    pub fn new(log_size: u32, step: usize) -> Self {
+        assert!(step > 0, "The step must be positive");
+        Self { log_size, step }
+    }
+}
+
+impl PreProcessedColumn for IsStep {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    fn id(&self) -> PreProcessedColumnId {
+        PreProcessedColumnId {
+            id: format!("preprocessed_is_step_{}_{}", self.step, self.log_size),
+        }
+    }
+
+    fn gen_column(&self) -> Vec<BaseField> {
+        let mut column = vec![BaseField::zero(); 1 << self.log_size];
+        for row in (0..column.len()).step_by(self.step) {
+            column[row] = BaseField::one();
+        }
+        column
+    }
+
+    fn eval_at_point(&self, point: CirclePoint<SecureField>) -> SecureField {
+        if !self.step.is_power_of_two() || self.step > 1 << self.log_size {
+            return interpolate_at_point(self, point);
+        }
+        if self.step == 1 {
+            return SecureField::one();
+        }
+        // The column is the vanishing polynomial of the coset divided by the one of its rows at
+        // the step. Doubling the offset from the first row `log_size - log_step` times maps the
+        // coset onto the subgroup of size `step`, and these rows onto its identity, where the
+        // ratio is the one of [IsFirst].
+        let log_step = self.step.ilog2();
+        let coset = CanonicCoset::new(self.log_size).coset();
+        let point = (point - coset.initial.into_ef()).repeated_double(self.log_size - log_step);
+        let subgroup = Coset::subgroup(log_step);
+        coset_vanishing(subgroup, point)
+            / (point_vanishing(subgroup.initial, point) * BaseField::from(self.step))
+    }
+}
+
+/// A column of the table of `a ^ b` for all `a` and `b` of `n_bits` bits.
+///
+/// Row `(a << n_bits) + b` holds `a` in column 0, `b` in column 1 and `a ^ b` in column 2.
+#[derive(Debug, Clone)]
+pub struct XorTable {
+    pub n_bits: u32,
+    pub col_index: usize,
+}
+
+impl XorTable {
+    pub fn new(n_bits: u32, col_index: usize) -> Self {
+        assert!(
+            col_index < 3,
+            "XOR table column index must be in range 0..3"
+        );
+        Self { n_bits, col_index }
+    }
+}
+
+impl PreProcessedColumn for XorTable {
+    fn log_size(&self) -> u32 {
+        2 * self.n_bits
+    }
+
+    fn id(&self) -> PreProcessedColumnId {
+        PreProcessedColumnId {
+            id: format!("preprocessed_xor_table_{}_{}", self.n_bits, self.col_index),
+        }
+    }
+
+    fn gen_column(&self) -> Vec<BaseField> {
+        let mask = (1 << self.n_bits) - 1;
+        (0..1 << self.log_size())
+            .map(|row: u32| {
+                let (a, b) = (row >> self.n_bits, row & mask);
+                BaseField::from([a, b, a ^ b][self.col_index])
+            })
+            .collect()
+    }
+}
+
+/// A column of the table of all tuples `(x_0, ..., x_{N-1})` with `0 <= x_i < 2^log_ranges[i]`.
+///
+/// Tuples are enumerated in lexicographic order, i.e. the last value changes at every row. Column
+/// `col_index` holds `x_{col_index}`.
+#[derive(Debug, Clone)]
+pub struct RangeCheck<const N: usize> {
+    pub log_ranges: [u32; N],
+    pub col_index: usize,
+}
+
+impl<const N: usize> RangeCheck<N> {
+    pub fn new(log_ranges: [u32; N], col_index: usize) -> Self {
+        assert!(
+            col_index < N,
+            "Range check column index must be in range 0..{N}"
+        );
+        Self {
+            log_ranges,
+            col_index,
+        }
+    }
+}
+
+impl<const N: usize> PreProcessedColumn for RangeCheck<N> {
+    fn log_size(&self) -> u32 {
+        self.log_ranges.iter().sum()
+    }
+
+    fn id(&self) -> PreProcessedColumnId {
+        PreProcessedColumnId {
+            id: format!(
+                "preprocessed_range_check_{}_column_{}",
+                self.log_ranges.iter().join("_"),
+                self.col_index
+            ),
+        }
+    }
+
+    fn gen_column(&self) -> Vec<BaseField> {
+        let shift: u32 = self.log_ranges[self.col_index + 1..].iter().sum();
+        let mask = (1 << self.log_ranges[self.col_index]) - 1;
+        (0..1 << self.log_size())
+            .map(|row: u32| BaseField::from((row >> shift) & mask))
+            .collect()
+    }
+}
+
+/// Checks the sampled values of the preprocessed trace against the evaluations of `columns`.
+///
+/// `columns` are in the order of the preprocessed trace, `column_log_sizes` are their committed
+/// log sizes, and `sample_points` and `sampled_values` the points each column is sampled at and
+/// its samples. Columns that no component uses have no samples.
+pub fn verify_preprocessed_columns(
+    columns: &[&dyn PreProcessedColumn],
+    column_log_sizes: &[u32],
+    sample_points: &ColumnVec<Vec<CirclePoint<SecureField>>>,
+    sampled_values: &ColumnVec<Vec<SecureField>>,
+) -> Result<(), VerificationError> {
+    if columns.len() != column_log_sizes.len()
+        || columns.len() != sample_points.len()
+        || columns.len() != sampled_values.len()
+    {
+        return Err(VerificationError::InvalidStructure(
+            "wrong number of preprocessed columns".to_string(),
+        ));
+    }
+    for (column_index, (column, &log_size, points, samples)) in
+        izip!(columns, column_log_sizes, sample_points, sampled_values).enumerate()
+    {
+        if column.log_size() != log_size {
+            return Err(VerificationError::InvalidStructure(format!(
+                "preprocessed column {column_index} has the wrong log size"
+            )));
+        }
+        if points.len() != samples.len() {
+            return Err(VerificationError::InvalidStructure(format!(
+                "preprocessed column {column_index} has {} samples at {} points",
+                samples.len(),
+                points.len()
+            )));
+        }
+        if zip(points, samples).any(|(&point, &sample)| sample != column.eval_at_point(point)) {
+            return Err(VerificationError::PreprocessedColumnNotMatching { column_index });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::{
+        interpolate_at_point, verify_preprocessed_columns, IsFirst, IsStep, PreProcessedColumn,
+        RangeCheck, Seq, XorTable,
+    };
+    use crate::circle::{CirclePoint, SECURE_FIELD_CIRCLE_GEN};
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::VerificationError;
+
+    fn oods_point() -> CirclePoint<SecureField> {
+        SECURE_FIELD_CIRCLE_GEN.mul(1 << 20)
+    }
+
+    fn to_u32s(column: &dyn PreProcessedColumn) -> Vec<u32> {
+        column.gen_column().iter().map(|value| value.0).collect()
+    }
+
+    #[test]
+    fn test_is_first_eval_at_point() {
+        for log_size in 1..8 {
+            let is_first = IsFirst::new(log_size);
+
+            assert_eq!(
+                is_first.eval_at_point(oods_point()),
+                interpolate_at_point(&is_first, oods_point())
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_step_eval_at_point() {
+        for log_size in 1..8 {
+            for step in [1, 2, 3, 4, 6, 8, 1 << log_size, 2 << log_size] {
+                let is_step = IsStep::new(log_size, step);
+
+                assert_eq!(
+                    is_step.eval_at_point(oods_point()),
+                    interpolate_at_point(&is_step, oods_point())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_gen_column() {
+        assert_eq!(to_u32s(&IsFirst::new(2)), [1, 0, 0, 0]);
+        assert_eq!(to_u32s(&Seq::new(2)), [0, 1, 2, 3]);
+        assert_eq!(to_u32s(&IsStep::new(3, 3)), [1, 0, 0, 1, 0, 0, 1, 0]);
+        assert_eq!(
+            [0, 1, 2].map(|col_index| to_u32s(&XorTable::new(1, col_index))),
+            [[0, 0, 1, 1], [0, 1, 0, 1], [0, 1, 1, 0]]
+        );
+        assert_eq!(
+            [0, 1].map(|col_index| to_u32s(&RangeCheck::new([1, 2], col_index))),
+            [[0, 0, 0, 0, 1, 1, 1, 1], [0, 1, 2, 3, 0, 1, 2, 3]]
+        );
+    }
+
+    #[test]
+    fn test_verify_preprocessed_columns() {
+        let is_first = IsFirst::new(4);
+        let seq = Seq::new(3);
+        let xor = XorTable::new(2, 2);
+        let columns: [&dyn PreProcessedColumn; 3] = [&is_first, &seq, &xor];
+        let column_log_sizes = [4, 3, 4];
+        let sample_points = vec![
+            vec![oods_point()],
+            vec![],
+            vec![oods_point(), oods_point().double()],
+        ];
+        let mut sampled_values = vec![
+            vec![is_first.eval_at_point(oods_point())],
+            vec![],
+            vec![
+                xor.eval_at_point(oods_point()),
+                xor.eval_at_point(oods_point().double()),
+            ],
+        ];
+
+        verify_preprocessed_columns(&columns, &column_log_sizes, &sample_points, &sampled_values)
+            .unwrap();
+
+        sampled_values[2][1] += SecureField::from(BaseField::from(1));
+        assert!(matches!(
+            verify_preprocessed_columns(
+                &columns,
+                &column_log_sizes,
+                &sample_points,
+                &sampled_values
+            ),
+            Err(VerificationError::PreprocessedColumnNotMatching { column_index: 2 })
+        ));
+        sampled_values[2].pop();
+        assert!(matches!(
+            verify_preprocessed_columns(
+                &columns,
+                &column_log_sizes,
+                &sample_points,
+                &sampled_values
+            ),
+            Err(VerificationError::InvalidStructure(_))
+        ));
+        assert!(matches!(
+            verify_preprocessed_columns(&columns, &[4, 4, 4], &sample_points, &sampled_values),
+            Err(VerificationError::InvalidStructure(_))
+        ));
+    }
+}
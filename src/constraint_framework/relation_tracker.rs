@@ -0,0 +1,411 @@
+//! Tracking of the entries components add to relations, to debug unbalanced lookups.
+//!
+//! [RelationTracker] evaluates components on their trace and records every [RelationEntry] they
+//! add through [EvalAtRow::add_to_relation]. Summing the multiplicities of every tuple of values
+//! per relation shows which tuples are used or yielded without a matching counterpart.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::ops::Mul;
+
+use indexmap_nostd::IndexMap as HashMap;
+use num_traits::Zero;
+
+use super::periodic_columns::PeriodicColumn;
+use super::{EvalAtRow, FrameworkComponent, FrameworkEval, Relation, RelationEntry};
+use crate::fields::m31::BaseField;
+use crate::fields::qm31::SecureField;
+use crate::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::lookups::utils::Fraction;
+use crate::pcs::TreeVec;
+use crate::ColumnVec;
+
+/// A tuple of values added to a relation at some row, with its multiplicity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelationTrackerEntry {
+    pub relation: String,
+    pub mult: SecureField,
+    pub values: Vec<BaseField>,
+}
+
+/// Evaluates a component at a row of its trace, and records the entries it adds to relations.
+///
+/// Constraints and logup fractions are ignored. Mask offsets wrap around the column size.
+pub struct RelationTrackerEvaluator<'a> {
+    pub trace: &'a TreeVec<ColumnVec<&'a Vec<BaseField>>>,
+    pub col_index: TreeVec<usize>,
+    pub row: usize,
+    pub entries: Vec<RelationTrackerEntry>,
+}
+
+impl<'a> RelationTrackerEvaluator<'a> {
+    pub fn new(trace: &'a TreeVec<ColumnVec<&'a Vec<BaseField>>>, row: usize) -> Self {
+        Self {
+            trace,
+            col_index: TreeVec::new(vec![0; trace.len()]),
+            row,
+            entries: vec![],
+        }
+    }
+}
+
+impl EvalAtRow for RelationTrackerEvaluator<'_> {
+    type F = BaseField;
+    type EF = SecureField;
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        interaction: usize,
+        offsets: [isize; N],
+    ) -> [Self::F; N] {
+        let col_index = self.col_index[interaction];
+        self.col_index[interaction] += 1;
+        let column = &self.trace[interaction][col_index];
+        offsets.map(|offset| {
+            // The mask row might wrap around the column size.
+            let row = (self.row as isize + offset).rem_euclid(column.len() as isize);
+            column[row as usize]
+        })
+    }
+
+    fn get_periodic_column(&mut self, column: &PeriodicColumn) -> Self::F {
+        column.at(self.row)
+    }
+
+    fn add_constraint<G>(&mut self, _constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+    }
+
+    fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
+        SecureField::from_m31_array(values)
+    }
+
+    fn add_to_relation<R: Relation<Self::F, Self::EF>>(
+        &mut self,
+        entry: RelationEntry<'_, Self::F, Self::EF, R>,
+    ) {
+        if entry.multiplicity.is_zero() {
+            return;
+        }
+        self.entries.push(RelationTrackerEntry {
+            relation: entry.relation.get_name().to_string(),
+            mult: entry.multiplicity,
+            values: entry.values.to_vec(),
+        });
+    }
+
+    fn set_logup_interaction(&mut self, _interaction: usize) {}
+
+    fn write_logup_frac(&mut self, _fraction: Fraction<Self::EF, Self::EF>) {}
+
+    fn finalize_logup_batched(&mut self, _batching: &super::Batching) {}
+
+    fn finalize_logup(&mut self) {}
+
+    fn finalize_logup_in_pairs(&mut self) {}
+
+    fn finalize_logup_by_degree(&mut self, _max_degree: usize) {}
+}
+
+/// Evaluates `eval` on every row of its trace, and returns the entries with a nonzero
+/// multiplicity it adds to relations.
+///
+/// `trace` holds the columns of the component only, in natural order.
+pub fn relation_entries<E: FrameworkEval>(
+    eval: &E,
+    trace: &TreeVec<ColumnVec<&Vec<BaseField>>>,
+) -> Vec<RelationTrackerEntry> {
+    (0..1 << eval.log_size())
+        .flat_map(|row| {
+            eval.evaluate(RelationTrackerEvaluator::new(trace, row))
+                .entries
+        })
+        .collect()
+}
+
+/// Collects the relation entries of several components.
+#[derive(Debug, Default)]
+pub struct RelationTracker {
+    pub entries: Vec<RelationTrackerEntry>,
+}
+
+impl RelationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the relation entries of `component` on its columns in `trace`.
+    ///
+    /// `trace` holds the columns of all components, as committed, but in natural order.
+    pub fn add_component<E: FrameworkEval>(
+        &mut self,
+        component: &FrameworkComponent<E>,
+        trace: &TreeVec<ColumnVec<Vec<BaseField>>>,
+    ) {
+        self.entries.extend(relation_entries(
+            &component.eval,
+            &component.component_trace(trace),
+        ));
+    }
+
+    pub fn summarize(&self) -> RelationSummary {
+        RelationSummary::summarize_relations(&self.entries)
+    }
+}
+
+/// Tuples of values added to a relation, with their total multiplicities.
+pub type RelationTuples = Vec<(Vec<BaseField>, SecureField)>;
+
+/// The total multiplicity of every tuple of values, per relation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelationSummary(pub Vec<(String, RelationTuples)>);
+
+impl RelationSummary {
+    /// Sums the multiplicities of equal tuples of the same relation. Trailing zeros of the tuples
+    /// are stripped, as they don't change the combined value.
+    pub fn summarize_relations(entries: &[RelationTrackerEntry]) -> Self {
+        let mut summary = HashMap::<String, HashMap<Vec<BaseField>, SecureField>>::new();
+        for entry in entries {
+            let mut values = entry.values.clone();
+            while values.last().is_some_and(|value| value.is_zero()) {
+                values.pop();
+            }
+            let mult = summary
+                .entry(entry.relation.clone())
+                .or_default()
+                .entry(values)
+                .or_default();
+            *mult += entry.mult;
+        }
+
+        Self(
+            summary
+                .into_iter()
+                .map(|(relation, tuples)| (relation, tuples.into_iter().collect()))
+                .collect(),
+        )
+    }
+
+    /// Returns the tuples of the relation named `relation`, with their total multiplicities.
+    pub fn get_relation_info(&self, relation: &str) -> Option<&[(Vec<BaseField>, SecureField)]> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == relation)
+            .map(|(_, tuples)| tuples.as_slice())
+    }
+
+    /// Returns the summary of the unbalanced tuples only, i.e. the ones with a nonzero total
+    /// multiplicity. Relations without such tuples are dropped.
+    pub fn cleaned(self) -> Self {
+        Self(
+            self.0
+                .into_iter()
+                .filter_map(|(relation, tuples)| {
+                    let tuples: Vec<_> = tuples
+                        .into_iter()
+                        .filter(|(_, mult)| !mult.is_zero())
+                        .collect();
+                    (!tuples.is_empty()).then_some((relation, tuples))
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns `true` if every tuple of every relation has a zero total multiplicity.
+    pub fn is_balanced(&self) -> bool {
+        self.0
+            .iter()
+            .all(|(_, tuples)| tuples.iter().all(|(_, mult)| mult.is_zero()))
+    }
+}
+
+impl Display for RelationSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (relation, tuples) in &self.0 {
+            writeln!(f, "{relation}:")?;
+            for (values, mult) in tuples {
+                write!(f, "  (")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                // Multiplicities are usually base field values.
+                match mult.to_m31_array() {
+                    [a, b, c, d] if b.is_zero() && c.is_zero() && d.is_zero() => {
+                        writeln!(f, ") -> {a}")?
+                    }
+                    _ => writeln!(f, ") -> {mult}")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use num_traits::{One, Zero};
+
+    use super::{relation_entries, RelationSummary, RelationTracker, RelationTrackerEntry};
+    use crate::constraint_framework::{
+        interaction_trace_idx, EvalAtRow, FrameworkComponent, FrameworkEval, RelationEntry,
+        TraceLocationAllocator, INTERACTION_TRACE_IDX,
+    };
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::pcs::TreeVec;
+
+    const LOG_SIZE: u32 = 2;
+
+    crate::relation!(ValueRelation, 2);
+
+    /// Yields its column to [ValueRelation] if `yields`, and uses it otherwise, with the logup
+    /// columns in the tree `logup_interaction`.
+    struct ValueEval {
+        relation: ValueRelation,
+        yields: bool,
+        logup_interaction: usize,
+    }
+
+    impl FrameworkEval for ValueEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn n_interaction_phases(&self) -> usize {
+            self.logup_interaction - INTERACTION_TRACE_IDX + 1
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            LOG_SIZE + 1
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let [value] = eval.next_interaction_mask(1, [0]);
+            let mult = match self.yields {
+                true => -E::EF::one(),
+                false => E::EF::one(),
+            };
+            eval.set_logup_interaction(self.logup_interaction);
+            eval.add_to_relation(RelationEntry::new(&self.relation, mult, &[value]));
+            eval.finalize_logup();
+            eval
+        }
+    }
+
+    fn entry(mult: u32, values: &[u32]) -> RelationTrackerEntry {
+        RelationTrackerEntry {
+            relation: "ValueRelation".to_string(),
+            mult: SecureField::from(BaseField::from(mult)),
+            values: values.iter().map(|v| BaseField::from(*v)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_relations() {
+        let entries = [entry(1, &[1, 2]), entry(2, &[1, 2, 0]), entry(3, &[4])];
+
+        let summary = RelationSummary::summarize_relations(&entries);
+
+        assert_eq!(
+            summary.get_relation_info("ValueRelation").unwrap(),
+            [
+                (
+                    vec![BaseField::from(1), BaseField::from(2)],
+                    BaseField::from(3).into()
+                ),
+                (vec![BaseField::from(4)], BaseField::from(3).into()),
+            ]
+        );
+        assert_eq!(summary.get_relation_info("OtherRelation"), None);
+        assert!(!summary.is_balanced());
+    }
+
+    #[test]
+    fn test_relation_tracker_reports_unbalanced_tuples() {
+        let column = |values: [u32; 1 << LOG_SIZE]| values.map(BaseField::from).to_vec();
+        let trace = TreeVec::new(vec![
+            vec![],
+            vec![column([1, 2, 3, 4]), column([4, 3, 2, 5])],
+            vec![column([0; 1 << LOG_SIZE]); 8],
+        ]);
+        let mut allocator = TraceLocationAllocator::default();
+        let components = [false, true].map(|yields| {
+            FrameworkComponent::new(
+                &mut allocator,
+                ValueEval {
+                    relation: ValueRelation::dummy(),
+                    yields,
+                    logup_interaction: INTERACTION_TRACE_IDX,
+                },
+                SecureField::zero(),
+            )
+        });
+
+        let mut tracker = RelationTracker::new();
+        for component in &components {
+            tracker.add_component(component, &trace);
+        }
+        let summary = tracker.summarize().cleaned();
+
+        assert_eq!(tracker.entries.len(), 8);
+        assert_eq!(
+            summary.0,
+            [(
+                "ValueRelation".to_string(),
+                vec![
+                    (vec![BaseField::from(1)], SecureField::one()),
+                    (vec![BaseField::from(5)], -SecureField::one()),
+                ]
+            )]
+        );
+        assert_eq!(
+            summary.to_string(),
+            format!(
+                "ValueRelation:\n  (1) -> 1\n  (5) -> {}\n",
+                -BaseField::one()
+            )
+        );
+    }
+
+    #[test]
+    fn test_relation_entries_with_logup_in_second_interaction_phase() {
+        let column = [1, 2, 3, 4].map(BaseField::from).to_vec();
+        let trace = TreeVec::new(vec![vec![], vec![&column]]);
+        let eval = ValueEval {
+            relation: ValueRelation::dummy(),
+            yields: false,
+            logup_interaction: interaction_trace_idx(1),
+        };
+
+        assert_eq!(
+            relation_entries(&eval, &trace),
+            [1, 2, 3, 4].map(|value| entry(1, &[value]))
+        );
+    }
+
+    #[test]
+    fn test_cleaned_drops_balanced_relations() {
+        let mut entries = vec![entry(1, &[7])];
+        entries.push(RelationTrackerEntry {
+            mult: -SecureField::one(),
+            ..entry(0, &[7])
+        });
+
+        let summary = RelationSummary::summarize_relations(&entries);
+
+        assert!(summary.is_balanced());
+        assert_eq!(summary.cleaned().0, Vec::new());
+    }
+}
@@ -12,12 +12,14 @@ use alloc::vec;
 use alloc::vec::Vec;
 use itertools::{zip_eq, Itertools};
 use num_traits::Zero;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use thiserror_no_std::Error;
 // use tracing::instrument;
 
 use super::backend::{ColumnOps, CpuBackend};
-use super::channel::{Channel, MerkleChannel};
+use super::channel::{ChallengeSource, MerkleChannel};
 use super::fields::m31::BaseField;
 use super::fields::qm31::{SecureField, QM31};
 use super::fields::secure_column::SECURE_EXTENSION_DEGREE;
@@ -30,8 +32,10 @@ use super::ColumnVec;
 use crate::circle::Coset;
 use crate::fft::ibutterfly;
 use crate::fields::FieldExpOps;
+use crate::parallel_iter;
 use crate::poly::circle::CanonicCoset;
 use crate::poly::line::LineDomain;
+use crate::proof_of_work::verify_pow;
 use crate::utils::bit_reverse_index;
 use crate::vcs::ops::MerkleHasher;
 use crate::vcs::prover::MerkleDecommitment;
@@ -367,9 +371,30 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
         config: FriConfig,
         proof: FriProof<MC::H>,
         column_bounds: Vec<CirclePolyDegreeBound>,
+    ) -> Result<Self, FriVerificationError> {
+        Self::commit_with_grinding(channel, config, proof, column_bounds, &[])
+    }
+
+    /// Same as [Self::commit], and also verifies the proofs of work the prover did on the layers
+    /// before their folding randomness was drawn.
+    ///
+    /// The proofs of work on a layer are checked in the order they appear in `grinding`.
+    pub fn commit_with_grinding(
+        channel: &mut MC::C,
+        config: FriConfig,
+        proof: FriProof<MC::H>,
+        column_bounds: Vec<CirclePolyDegreeBound>,
+        grinding: &[FriLayerGrinding],
     ) -> Result<Self, FriVerificationError> {
         assert!(column_bounds.is_sorted_by_key(|b| Reverse(*b)));
+        let n_layers = 1 + proof.inner_layers.len();
+        if let Some(grinding) = grinding.iter().find(|grinding| grinding.layer >= n_layers) {
+            return Err(FriVerificationError::LayerProofOfWorkInvalid {
+                layer: grinding.layer,
+            });
+        }
         MC::mix_root(channel, proof.first_layer.commitment);
+        verify_layer_grinding(channel, grinding, 0)?;
 
         let max_column_bound = column_bounds[0];
         let column_commitment_domains = column_bounds
@@ -395,6 +420,7 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
 
         for (layer_index, proof) in proof.inner_layers.into_iter().enumerate() {
             MC::mix_root(channel, proof.commitment);
+            verify_layer_grinding(channel, grinding, layer_index + 1)?;
 
             inner_layers.push(FriInnerLayerVerifier {
                 degree_bound: layer_bound,
@@ -418,7 +444,10 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
         let last_layer_poly = proof.last_layer_poly;
 
         if last_layer_poly.len() > (1 << config.log_last_layer_degree_bound) {
-            return Err(FriVerificationError::LastLayerDegreeInvalid);
+            return Err(FriVerificationError::LastLayerDegreeInvalid {
+                log_degree_bound: config.log_last_layer_degree_bound,
+                n_coeffs: last_layer_poly.len(),
+            });
         }
 
         channel.mix_felts(&last_layer_poly);
@@ -543,7 +572,9 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
             let x = domain.at(bit_reverse_index(query, domain.log_size()));
 
             if query_eval != last_layer_poly.eval_at_point(x.into()) {
-                return Err(FriVerificationError::LastLayerEvaluationsInvalid);
+                return Err(FriVerificationError::LastLayerEvaluationsInvalid {
+                    query_position: query,
+                });
             }
         }
 
@@ -596,25 +627,103 @@ fn get_query_positions_by_log_size(
         .collect()
 }
 
-#[derive(Clone, Copy, Debug, Error)]
+/// A proof of work on a FRI layer, done after the layer is committed and before its folding
+/// randomness is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FriLayerGrinding {
+    /// Index of the layer. Layer 0 is the first layer, layer `i > 0` is inner layer `i - 1`.
+    pub layer: usize,
+    pub pow_bits: u32,
+    pub nonce: u64,
+}
+
+/// Verifies the proofs of work on `layer`, in order.
+fn verify_layer_grinding(
+    channel: &mut impl ChallengeSource,
+    grinding: &[FriLayerGrinding],
+    layer: usize,
+) -> Result<(), FriVerificationError> {
+    grinding
+        .iter()
+        .filter(|grinding| grinding.layer == layer)
+        .try_for_each(|grinding| {
+            if verify_pow(channel, grinding.nonce, grinding.pow_bits) {
+                Ok(())
+            } else {
+                Err(FriVerificationError::LayerProofOfWorkInvalid { layer })
+            }
+        })
+}
+
+/// A failed FRI check, with the layer and query position where it was detected.
+///
+/// Query positions are indices into the bit-reversed evaluation domain of the layer (or of the
+/// column, in the first layer).
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
 pub enum FriVerificationError {
     #[error("proof contains an invalid number of FRI layers")]
     InvalidNumFriLayers,
-    #[error("evaluations are invalid in the first layer")]
-    FirstLayerEvaluationsInvalid,
-    #[error("queries do not resolve to their commitment in the first layer")]
+    #[error(
+        "evaluations are invalid in the first layer: witness is too short at position \
+        {query_position} of the column of log size {column_log_size}"
+    )]
+    FirstLayerEvaluationsInvalid {
+        column_log_size: u32,
+        query_position: usize,
+    },
+    #[error("evaluations are invalid in the first layer: witness is too long")]
+    FirstLayerWitnessTooLong,
+    #[error("queries do not resolve to their commitment in the first layer: {error}")]
     FirstLayerCommitmentInvalid { error: MerkleVerificationError },
-    #[error("queries do not resolve to their commitment in inner layer {inner_layer}")]
+    #[error("queries do not resolve to their commitment in inner layer {inner_layer}: {error}")]
     InnerLayerCommitmentInvalid {
         inner_layer: usize,
         error: MerkleVerificationError,
     },
-    #[error("evaluations are invalid in inner layer {inner_layer}")]
-    InnerLayerEvaluationsInvalid { inner_layer: usize },
-    #[error("degree of last layer is invalid")]
-    LastLayerDegreeInvalid,
-    #[error("evaluations in the last layer are invalid")]
-    LastLayerEvaluationsInvalid,
+    #[error(
+        "evaluations are invalid in inner layer {inner_layer}: witness is too short at position \
+        {query_position} of the layer of log size {layer_log_size}"
+    )]
+    InnerLayerEvaluationsInvalid {
+        inner_layer: usize,
+        layer_log_size: u32,
+        query_position: usize,
+    },
+    #[error("evaluations are invalid in inner layer {inner_layer}: witness is too long")]
+    InnerLayerWitnessTooLong { inner_layer: usize },
+    #[error(
+        "degree of last layer is invalid: {n_coeffs} coefficients for log degree bound \
+        {log_degree_bound}"
+    )]
+    LastLayerDegreeInvalid {
+        log_degree_bound: u32,
+        n_coeffs: usize,
+    },
+    #[error("evaluations in the last layer are invalid at query position {query_position}")]
+    LastLayerEvaluationsInvalid { query_position: usize },
+    #[error("proof of work on layer {layer} is invalid or the layer does not exist")]
+    LayerProofOfWorkInvalid { layer: usize },
+}
+
+impl FriVerificationError {
+    /// A stable numeric code identifying the variant. Codes are never reused or reassigned.
+    ///
+    /// Use [MerkleVerificationError::code] on the nested error for the cause of a commitment
+    /// failure.
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::InvalidNumFriLayers => 200,
+            Self::FirstLayerEvaluationsInvalid { .. } => 201,
+            Self::FirstLayerCommitmentInvalid { .. } => 202,
+            Self::InnerLayerCommitmentInvalid { .. } => 203,
+            Self::InnerLayerEvaluationsInvalid { .. } => 204,
+            Self::LastLayerDegreeInvalid { .. } => 205,
+            Self::LastLayerEvaluationsInvalid { .. } => 206,
+            Self::FirstLayerWitnessTooLong => 207,
+            Self::InnerLayerWitnessTooLong { .. } => 208,
+            Self::LayerProofOfWorkInvalid { .. } => 209,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -741,8 +850,11 @@ impl<H: MerkleHasher> FriFirstLayerVerifier<H> {
                     &mut fri_witness,
                     CIRCLE_TO_LINE_FOLD_STEP,
                 )
-                .map_err(|InsufficientWitnessError| {
-                    FriVerificationError::FirstLayerEvaluationsInvalid
+                .map_err(|InsufficientWitnessError { position }| {
+                    FriVerificationError::FirstLayerEvaluationsInvalid {
+                        column_log_size: column_domain.log_size(),
+                        query_position: position,
+                    }
                 })?;
 
             // Columns of the same size have the same decommitment positions.
@@ -761,7 +873,7 @@ impl<H: MerkleHasher> FriFirstLayerVerifier<H> {
 
         // Check all proof evals have been consumed.
         if fri_witness.next().is_some() {
-            return Err(FriVerificationError::FirstLayerEvaluationsInvalid);
+            return Err(FriVerificationError::FirstLayerWitnessTooLong);
         }
 
         let merkle_verifier = MerkleVerifier::new(
@@ -822,15 +934,17 @@ impl<H: MerkleHasher> FriInnerLayerVerifier<H> {
                 &mut fri_witness,
                 FOLD_STEP,
             )
-            .map_err(|InsufficientWitnessError| {
+            .map_err(|InsufficientWitnessError { position }| {
                 FriVerificationError::InnerLayerEvaluationsInvalid {
                     inner_layer: self.layer_index,
+                    layer_log_size: self.domain.log_size(),
+                    query_position: position,
                 }
             })?;
 
         // Check all proof evals have been consumed.
         if fri_witness.next().is_some() {
-            return Err(FriVerificationError::InnerLayerEvaluationsInvalid {
+            return Err(FriVerificationError::InnerLayerWitnessTooLong {
                 inner_layer: self.layer_index,
             });
         }
@@ -1053,7 +1167,9 @@ fn compute_decommitment_positions_and_rebuild_evals(
         let subset_eval = subset_decommitment_positions
             .map(|position| match subset_queries_iter.next_if_eq(&position) {
                 Some(_) => Ok(query_evals.next().unwrap()),
-                None => witness_evals.next().ok_or(InsufficientWitnessError),
+                None => witness_evals
+                    .next()
+                    .ok_or(InsufficientWitnessError { position }),
             })
             .collect::<Result<_, _>>()?;
 
@@ -1066,8 +1182,11 @@ fn compute_decommitment_positions_and_rebuild_evals(
     Ok((decommitment_positions, sparse_evaluation))
 }
 
+/// The witness ran out while rebuilding the evaluation at `position`.
 #[derive(Debug)]
-struct InsufficientWitnessError;
+struct InsufficientWitnessError {
+    position: usize,
+}
 
 /// Foldable subsets of evaluations on a [`CirclePoly`] or [`LinePoly`].
 ///
@@ -1094,7 +1213,8 @@ impl SparseEvaluation {
     }
 
     fn fold_line(self, fold_alpha: SecureField, source_domain: LineDomain) -> Vec<SecureField> {
-        zip(self.subset_evals, self.subset_domain_initial_indexes)
+        parallel_iter!(self.subset_evals)
+            .zip(self.subset_domain_initial_indexes)
             .map(|(eval, domain_initial_index)| {
                 let fold_domain_initial = source_domain.coset().index_at(domain_initial_index);
                 let fold_domain = LineDomain::new(Coset::new(fold_domain_initial, FOLD_STEP));
@@ -1105,7 +1225,8 @@ impl SparseEvaluation {
     }
 
     fn fold_circle(self, fold_alpha: SecureField, source_domain: CircleDomain) -> Vec<SecureField> {
-        zip(self.subset_evals, self.subset_domain_initial_indexes)
+        parallel_iter!(self.subset_evals)
+            .zip(self.subset_domain_initial_indexes)
             .map(|(eval, domain_initial_index)| {
                 let fold_domain_initial = source_domain.index_at(domain_initial_index);
                 let fold_domain = CircleDomain::new(Coset::new(
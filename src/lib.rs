@@ -2,20 +2,22 @@
 
 use core::ops::{Deref, DerefMut};
 
-use alloc::{string::String, vec};
+use alloc::{format, string::String, vec};
 use alloc::{string::ToString, vec::Vec};
-use array_init::try_array_init;
 use serde::{Deserialize, Serialize};
 use thiserror_no_std::Error;
 
 use crate::{
     air::{Component, Components},
-    channel::{Channel, MerkleChannel},
+    channel::{ChallengeError, ChallengeSource, MerkleChannel},
     circle::CirclePoint,
-    constraint_framework::PREPROCESSED_TRACE_IDX,
+    constraint_framework::{
+        preprocessed_columns::{verify_preprocessed_columns, PreProcessedColumn},
+        PREPROCESSED_TRACE_IDX,
+    },
     fields::{qm31::SecureField, secure_column::SECURE_EXTENSION_DEGREE},
     fri::FriVerificationError,
-    pcs::{CommitmentSchemeProof, CommitmentSchemeVerifier},
+    pcs::{CommitmentSchemeProof, CommitmentSchemeVerifier, GrindingPoint, PolicyViolation},
     vcs::{ops::MerkleHasher, verifier::MerkleVerificationError},
 };
 
@@ -90,12 +92,90 @@ macro_rules! parallel_iter {
     }};
 }
 
+/// Verifies a STARK proof of the given components.
+///
+/// Challenges are taken from `channel`, the [ChallengeSource] of `MC`. This is a Fiat-Shamir
+/// [Channel](channel::Channel) for non-interactive proofs, or an
+/// [InteractiveChannel](channel::InteractiveChannel) fed by an external message queue.
 pub fn verify<MC: MerkleChannel>(
     components: &[&dyn Component],
     channel: &mut MC::C,
     commitment_scheme: &mut CommitmentSchemeVerifier<MC>,
     proof: StarkProof<MC::H>,
 ) -> Result<(), VerificationError> {
+    verify_with_format(
+        components,
+        channel,
+        commitment_scheme,
+        proof,
+        CompositionPolyFormat::default(),
+    )
+}
+
+/// Same as [verify], for proofs that commit to the composition polynomial in the given `format`.
+pub fn verify_with_format<MC: MerkleChannel>(
+    components: &[&dyn Component],
+    channel: &mut MC::C,
+    commitment_scheme: &mut CommitmentSchemeVerifier<MC>,
+    proof: StarkProof<MC::H>,
+    format: CompositionPolyFormat,
+) -> Result<(), VerificationError> {
+    verify_impl(components, channel, commitment_scheme, proof, format, None)
+}
+
+/// Same as [verify_with_format], but also checks the sampled values of the preprocessed trace
+/// against the evaluations of `preprocessed_columns`, so that the preprocessed trace doesn't have
+/// to be trusted. `preprocessed_columns` must be in the order of the preprocessed trace. See
+/// [verify_preprocessed_columns].
+pub fn verify_with_preprocessed_columns<MC: MerkleChannel>(
+    components: &[&dyn Component],
+    channel: &mut MC::C,
+    commitment_scheme: &mut CommitmentSchemeVerifier<MC>,
+    proof: StarkProof<MC::H>,
+    format: CompositionPolyFormat,
+    preprocessed_columns: &[&dyn PreProcessedColumn],
+) -> Result<(), VerificationError> {
+    verify_impl(
+        components,
+        channel,
+        commitment_scheme,
+        proof,
+        format,
+        Some(preprocessed_columns),
+    )
+}
+
+fn verify_impl<MC: MerkleChannel>(
+    components: &[&dyn Component],
+    channel: &mut MC::C,
+    commitment_scheme: &mut CommitmentSchemeVerifier<MC>,
+    proof: StarkProof<MC::H>,
+    format: CompositionPolyFormat,
+    preprocessed_columns: Option<&[&dyn PreProcessedColumn]>,
+) -> Result<(), VerificationError> {
+    let result = verify_proof(
+        components,
+        channel,
+        commitment_scheme,
+        proof,
+        format,
+        preprocessed_columns,
+    );
+    // A malformed challenge is replaced by zeroes, which may fail a later check first.
+    channel
+        .malformed_challenge()
+        .map_or(result, |error| Err(error.into()))
+}
+
+fn verify_proof<MC: MerkleChannel>(
+    components: &[&dyn Component],
+    channel: &mut MC::C,
+    commitment_scheme: &mut CommitmentSchemeVerifier<MC>,
+    proof: StarkProof<MC::H>,
+    format: CompositionPolyFormat,
+    preprocessed_columns: Option<&[&dyn PreProcessedColumn]>,
+) -> Result<(), VerificationError> {
+    commitment_scheme.check_policy()?;
     let n_preprocessed_columns = commitment_scheme.trees[PREPROCESSED_TRACE_IDX]
         .column_log_sizes
         .len();
@@ -103,25 +183,50 @@ pub fn verify<MC: MerkleChannel>(
         components: components.to_vec(),
         n_preprocessed_columns,
     };
+    // The trees committed so far are the preprocessed trace, the original trace and the trees of
+    // all the interaction phases.
+    let n_trace_trees = commitment_scheme.trees.len();
     let random_coeff = channel.draw_felt();
 
     // Read composition polynomial commitment.
+    let composition_log_size = components.composition_log_degree_bound();
+    let Some(composition_column_log_sizes) = format.column_log_sizes(composition_log_size) else {
+        return Err(VerificationError::InvalidStructure(format!(
+            "composition polynomial of log size {composition_log_size} is too small for the \
+             {format:?} format"
+        )));
+    };
     commitment_scheme.commit(
         *proof.commitments.last().unwrap(),
-        &[components.composition_log_degree_bound(); SECURE_EXTENSION_DEGREE],
+        &composition_column_log_sizes,
         channel,
     );
 
-    // Draw OODS point.
+    // Verify the proofs of work done before the OODS point, then draw it.
+    commitment_scheme.verify_grinding(
+        GrindingPoint::BeforeOodsPoint,
+        &proof.grinding_nonces,
+        channel,
+    )?;
     let oods_point = CirclePoint::<SecureField>::get_random_point(channel);
     // Get mask sample points relative to oods point.
     let mut sample_points = components.mask_points(oods_point);
+    if sample_points.len() > n_trace_trees {
+        return Err(VerificationError::InvalidStructure(format!(
+            "components read {} trees, but only {n_trace_trees} trees are committed",
+            sample_points.len()
+        )));
+    }
+    // Trees no component reads from, e.g. of an unused interaction phase, aren't sampled.
+    sample_points.resize(n_trace_trees, vec![]);
     // Add the composition polynomial mask points.
-    sample_points.push(vec![vec![oods_point]; SECURE_EXTENSION_DEGREE]);
+    sample_points.push(vec![vec![oods_point]; format.n_columns()]);
 
-    let composition_oods_eval = proof.extract_composition_oods_eval().map_err(|_| {
-        VerificationError::InvalidStructure("Unexpected sampled_values structure".to_string())
-    })?;
+    let composition_oods_eval = proof
+        .extract_composition_oods_eval(oods_point, composition_log_size, format)
+        .map_err(|_| {
+            VerificationError::InvalidStructure("Unexpected sampled_values structure".to_string())
+        })?;
     if composition_oods_eval
         != components.eval_composition_polynomial_at_point(
             oods_point,
@@ -131,9 +236,88 @@ pub fn verify<MC: MerkleChannel>(
     {
         return Err(VerificationError::OodsNotMatching);
     }
+    if let Some(preprocessed_columns) = preprocessed_columns {
+        verify_preprocessed_columns(
+            preprocessed_columns,
+            &commitment_scheme.trees[PREPROCESSED_TRACE_IDX].column_log_sizes,
+            &sample_points[PREPROCESSED_TRACE_IDX],
+            &proof.sampled_values[PREPROCESSED_TRACE_IDX],
+        )?;
+    }
     commitment_scheme.verify_values(sample_points, proof.0, channel)
 }
 
+/// How the prover commits to the composition polynomial, which depends on the stwo version that
+/// produced the proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompositionPolyFormat {
+    /// The composition polynomial is committed as a whole, in [SECURE_EXTENSION_DEGREE] columns of
+    /// size `composition_log_size`. This is the format of stwo rev a194fad.
+    #[default]
+    Single,
+    /// The composition polynomial `p` is split into two halves `left` and `right` of size
+    /// `composition_log_size - 1`, committed in `2 * SECURE_EXTENSION_DEGREE` columns, such that
+    /// `p(P) = left(P) + pi^(composition_log_size - 2)(P).x * right(P)`, where `pi` is the circle
+    /// doubling map. This is the format of newer stwo releases.
+    Split,
+}
+
+impl CompositionPolyFormat {
+    /// The number of committed composition columns.
+    pub const fn n_columns(self) -> usize {
+        match self {
+            Self::Single => SECURE_EXTENSION_DEGREE,
+            Self::Split => 2 * SECURE_EXTENSION_DEGREE,
+        }
+    }
+
+    /// The smallest composition log size the format supports.
+    pub const fn min_composition_log_size(self) -> u32 {
+        match self {
+            Self::Single => 0,
+            // The recombination doubles the point `composition_log_size - 2` times.
+            Self::Split => 2,
+        }
+    }
+
+    /// The log sizes of the committed composition columns, or `None` if `composition_log_size`
+    /// is below [Self::min_composition_log_size].
+    pub fn column_log_sizes(self, composition_log_size: u32) -> Option<Vec<u32>> {
+        if composition_log_size < self.min_composition_log_size() {
+            return None;
+        }
+        Some(match self {
+            Self::Single => vec![composition_log_size; SECURE_EXTENSION_DEGREE],
+            Self::Split => vec![composition_log_size - 1; 2 * SECURE_EXTENSION_DEGREE],
+        })
+    }
+
+    /// Recombines the evaluations of the composition columns at `point` into the evaluation of the
+    /// composition polynomial.
+    pub fn combine_evals(
+        self,
+        point: CirclePoint<SecureField>,
+        composition_log_size: u32,
+        column_evals: &[SecureField],
+    ) -> Result<SecureField, InvalidOodsSampleStructure> {
+        if column_evals.len() != self.n_columns() {
+            return Err(InvalidOodsSampleStructure);
+        }
+        let (left_evals, right_evals) = column_evals.split_at(SECURE_EXTENSION_DEGREE);
+        let left_eval = SecureField::from_partial_evals(left_evals.try_into().unwrap());
+        match self {
+            Self::Single => Ok(left_eval),
+            Self::Split => {
+                let n_doubles = composition_log_size
+                    .checked_sub(self.min_composition_log_size())
+                    .ok_or(InvalidOodsSampleStructure)?;
+                let right_eval = SecureField::from_partial_evals(right_evals.try_into().unwrap());
+                Ok(left_eval + point.repeated_double(n_doubles).x * right_eval)
+            }
+        }
+    }
+}
+
 /// Error when the sampled values have an invalid structure.
 #[derive(Clone, Copy, Debug)]
 pub struct InvalidOodsSampleStructure;
@@ -148,8 +332,11 @@ pub enum ProvingError {
 pub enum VerificationError {
     #[error("Proof has invalid structure: {0}.")]
     InvalidStructure(String),
-    #[error(transparent)]
-    Merkle(#[from] MerkleVerificationError),
+    #[error("Merkle decommitment of tree {tree_index} is invalid: {error}.")]
+    Merkle {
+        tree_index: usize,
+        error: MerkleVerificationError,
+    },
     #[error(
         "The composition polynomial OODS value does not match the trace OODS values
     (DEEP-ALI failure)."
@@ -159,6 +346,33 @@ pub enum VerificationError {
     Fri(#[from] FriVerificationError),
     #[error("Proof of work verification failed.")]
     ProofOfWork,
+    #[error(
+        "A sampled value of preprocessed column {column_index} does not match its evaluation."
+    )]
+    PreprocessedColumnNotMatching { column_index: usize },
+    #[error("Security policy violated: {0}.")]
+    PolicyViolation(#[from] PolicyViolation),
+    #[error("Malformed challenge: {0}.")]
+    Challenge(#[from] ChallengeError),
+}
+
+impl VerificationError {
+    /// A stable numeric code identifying the failure, suitable for compact failure reasons.
+    ///
+    /// Codes are never reused or reassigned. Merkle, FRI, policy and challenge failures return the
+    /// code of the nested error (100s, 200s, 300s and 400s respectively).
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::InvalidStructure(_) => 1,
+            Self::OodsNotMatching => 2,
+            Self::ProofOfWork => 3,
+            Self::PreprocessedColumnNotMatching { .. } => 4,
+            Self::Merkle { error, .. } => error.code(),
+            Self::Fri(error) => error.code(),
+            Self::PolicyViolation(violation) => violation.code(),
+            Self::Challenge(error) => error.code(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -166,26 +380,26 @@ pub struct StarkProof<H: MerkleHasher>(pub CommitmentSchemeProof<H>);
 
 impl<H: MerkleHasher> StarkProof<H> {
     /// Extracts the composition trace Out-Of-Domain-Sample evaluation from the mask.
-    fn extract_composition_oods_eval(&self) -> Result<SecureField, InvalidOodsSampleStructure> {
+    fn extract_composition_oods_eval(
+        &self,
+        oods_point: CirclePoint<SecureField>,
+        composition_log_size: u32,
+        format: CompositionPolyFormat,
+    ) -> Result<SecureField, InvalidOodsSampleStructure> {
         // TODO(andrew): `[.., composition_mask, _quotients_mask]` when add quotients commitment.
         let [.., composition_mask] = &**self.sampled_values else {
             return Err(InvalidOodsSampleStructure);
         };
 
-        let mut composition_cols = composition_mask.iter();
-
-        let coordinate_evals = try_array_init(|_| {
-            let col = &**composition_cols.next().ok_or(InvalidOodsSampleStructure)?;
-            let [eval] = col.try_into().map_err(|_| InvalidOodsSampleStructure)?;
-            Ok(eval)
-        })?;
-
-        // Too many columns.
-        if composition_cols.next().is_some() {
-            return Err(InvalidOodsSampleStructure);
-        }
+        let column_evals = composition_mask
+            .iter()
+            .map(|col| {
+                let [eval] = (**col).try_into().map_err(|_| InvalidOodsSampleStructure)?;
+                Ok(eval)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(SecureField::from_partial_evals(coordinate_evals))
+        format.combine_evals(oods_point, composition_log_size, &column_evals)
     }
 }
 
@@ -196,3 +410,86 @@ impl<H: MerkleHasher> Deref for StarkProof<H> {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::array;
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use itertools::Itertools;
+    use num_traits::One;
+
+    use super::CompositionPolyFormat;
+    use crate::backend::CpuBackend;
+    use crate::channel::Blake2sChannel;
+    use crate::circle::CirclePoint;
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::poly::circle::CirclePoly;
+
+    const LOG_SIZE: u32 = 5;
+
+    #[test]
+    fn test_combine_split_composition_evals() {
+        let point = CirclePoint::<SecureField>::get_random_point(&mut Blake2sChannel::default());
+        let coordinate_polys: [CirclePoly<CpuBackend>; 4] = array::from_fn(|i| {
+            CirclePoly::new(
+                (0..1 << LOG_SIZE)
+                    .map(|j| BaseField::from_u32_unchecked((i * 97 + j * j) as u32))
+                    .collect(),
+            )
+        });
+        let expected = SecureField::from_partial_evals(
+            coordinate_polys.each_ref().map(|p| p.eval_at_point(point)),
+        );
+        let (left_polys, right_polys): (Vec<_>, Vec<_>) = coordinate_polys
+            .iter()
+            .map(|poly| {
+                let (left, right) = poly.coeffs.split_at(1 << (LOG_SIZE - 1));
+                (
+                    CirclePoly::<CpuBackend>::new(left.to_vec()),
+                    CirclePoly::<CpuBackend>::new(right.to_vec()),
+                )
+            })
+            .unzip();
+        let split_evals = left_polys
+            .iter()
+            .chain(&right_polys)
+            .map(|poly| poly.eval_at_point(point))
+            .collect_vec();
+        let single_evals = coordinate_polys
+            .iter()
+            .map(|poly| poly.eval_at_point(point))
+            .collect_vec();
+
+        let split_eval = CompositionPolyFormat::Split.combine_evals(point, LOG_SIZE, &split_evals);
+        let single_eval =
+            CompositionPolyFormat::Single.combine_evals(point, LOG_SIZE, &single_evals);
+
+        assert_eq!(split_eval.unwrap(), expected);
+        assert_eq!(single_eval.unwrap(), expected);
+        assert!(CompositionPolyFormat::Split
+            .combine_evals(point, LOG_SIZE, &single_evals)
+            .is_err());
+    }
+
+    #[test]
+    fn test_split_composition_log_size_guard() {
+        let point = CirclePoint::<SecureField>::get_random_point(&mut Blake2sChannel::default());
+        let evals = [SecureField::one(); 8];
+
+        assert_eq!(
+            CompositionPolyFormat::Split.column_log_sizes(2),
+            Some(vec![1; 8])
+        );
+        assert_eq!(CompositionPolyFormat::Split.column_log_sizes(1), None);
+        assert_eq!(
+            CompositionPolyFormat::Single.column_log_sizes(0),
+            Some(vec![0; 4])
+        );
+        assert!(CompositionPolyFormat::Split
+            .combine_evals(point, 1, &evals)
+            .is_err());
+    }
+}
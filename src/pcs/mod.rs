@@ -6,15 +6,29 @@
 //! the unique decoding regime. This is enough for a STARK proof though, where we only want to imply
 //! the existence of such polynomials, and are ok with having a small decoding list.
 //! Note: Opened points cannot come from the commitment domain.
+//!
+//! The scheme can be used on its own, outside of [`verify`](crate::verify):
+//! 1. Create a [CommitmentSchemeVerifier] and call [CommitmentSchemeVerifier::commit] with the root
+//!    and column log sizes of each tree, in the order the prover committed them.
+//! 2. Draw the opening points from the channel (or agree on them in any other way that happens
+//!    after the commitments). If the config has grinding at [GrindingPoint::BeforeOodsPoint],
+//!    verify it first with [CommitmentSchemeVerifier::verify_grinding].
+//! 3. Call [CommitmentSchemeVerifier::verify_opening] with the points and claimed values of each
+//!    column, and the [PcsOpeningProof]. A [CommitmentSchemeProof] can be split into the values and
+//!    the opening proof with [CommitmentSchemeProof::into_opening].
 
+mod policy;
 mod prover;
 pub mod quotients;
 mod utils;
 mod verifier;
 
+use alloc::vec::Vec;
+
 use serde::{Deserialize, Serialize};
 
-pub use self::prover::CommitmentSchemeProof;
+pub use self::policy::{PolicyViolation, SecurityPolicy};
+pub use self::prover::{CommitmentSchemeProof, PcsOpeningProof};
 pub use self::utils::TreeVec;
 pub use self::verifier::CommitmentSchemeVerifier;
 use super::fri::FriConfig;
@@ -26,14 +40,82 @@ pub struct TreeSubspan {
     pub col_end: usize,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// A transcript position at which the prover grinds, in addition to the proof of work done before
+/// the FRI queries are sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GrindingPoint {
+    /// After the composition polynomial is committed, before the OODS point is drawn.
+    BeforeOodsPoint,
+    /// After a FRI layer is committed, before its folding randomness is drawn. Layer 0 is the first
+    /// layer, layer `i > 0` is inner layer `i - 1`.
+    FriLayer(usize),
+}
+
+/// A proof of work of `pow_bits` bits at `point`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grinding {
+    pub point: GrindingPoint,
+    pub pow_bits: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PcsConfig {
+    /// Bits of the proof of work done before the FRI queries are sampled.
     pub pow_bits: u32,
     pub fri_config: FriConfig,
+    /// Additional proofs of work, in the order their nonces appear in the proof. The number of
+    /// grinding points isn't bounded, which is why the config isn't `Copy`.
+    #[serde(default)]
+    pub grinding: Vec<Grinding>,
 }
 impl PcsConfig {
-    pub const fn security_bits(&self) -> u32 {
-        self.pow_bits + self.fri_config.security_bits()
+    /// The security of the scheme: the security of the FRI queries, with the bits of every
+    /// [Grinding] added to it.
+    ///
+    /// Each grinding point protects the round of the challenge drawn after it, see
+    /// [Self::security_bits_per_round]. Adding them up assumes each of these rounds has a soundness
+    /// error no larger than the one of the FRI queries.
+    pub fn security_bits(&self) -> u32 {
+        self.security_bits_per_round().total()
+    }
+
+    /// The security bits of each round, with each [Grinding] counted in the round it protects.
+    pub fn security_bits_per_round(&self) -> RoundSecurityBits {
+        let mut rounds = RoundSecurityBits {
+            before_oods_point: 0,
+            fri_layers: Vec::new(),
+            queries: self.pow_bits + self.fri_config.security_bits(),
+        };
+        for grinding in &self.grinding {
+            match grinding.point {
+                GrindingPoint::BeforeOodsPoint => rounds.before_oods_point += grinding.pow_bits,
+                GrindingPoint::FriLayer(layer) => {
+                    if rounds.fri_layers.len() <= layer {
+                        rounds.fri_layers.resize(layer + 1, 0);
+                    }
+                    rounds.fri_layers[layer] += grinding.pow_bits;
+                }
+            }
+        }
+        rounds
+    }
+}
+
+/// The security bits of the rounds of the scheme, see [PcsConfig::security_bits_per_round].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundSecurityBits {
+    /// Bits of the grinding before the OODS point is drawn.
+    pub before_oods_point: u32,
+    /// Bits of the grinding before the folding randomness of each FRI layer is drawn, by layer.
+    pub fri_layers: Vec<u32>,
+    /// Security of the FRI queries, including the proof of work done before they are sampled.
+    pub queries: u32,
+}
+
+impl RoundSecurityBits {
+    /// The bits of all the rounds added up.
+    pub fn total(&self) -> u32 {
+        self.before_oods_point + self.fri_layers.iter().sum::<u32>() + self.queries
     }
 }
 
@@ -42,19 +124,201 @@ impl Default for PcsConfig {
         Self {
             pow_bits: 5,
             fri_config: FriConfig::new(0, 1, 3),
+            grinding: Vec::new(),
         }
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     #[test]
-//     fn test_security_bits() {
-//         let config = super::PcsConfig {
-//             pow_bits: 42,
-//             fri_config: super::FriConfig::new(10, 10, 70),
-//         };
-//         // 10 * 70 + 42 = 742
-//         assert!(config.security_bits() == 742);
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::{
+        CommitmentSchemeVerifier, Grinding, GrindingPoint, PcsConfig, PcsOpeningProof,
+        RoundSecurityBits, TreeVec,
+    };
+    use crate::backend::CpuBackend;
+    use crate::channel::{Blake2sChannel, Channel, MerkleChannel};
+    use crate::fields::qm31::SecureField;
+    use crate::fri::{
+        CirclePolyDegreeBound, FriConfig, FriLayerGrinding, FriLayerProof, FriProof,
+        FriVerificationError, FriVerifier,
+    };
+    use crate::poly::line::LinePoly;
+    use crate::proof_of_work::{verify_pow, GrindOps};
+    use crate::vcs::blake2_hash::Blake2sHash;
+    use crate::vcs::blake2_merkle::{Blake2sMerkleChannel, Blake2sMerkleHasher};
+    use crate::vcs::prover::MerkleDecommitment;
+    use crate::VerificationError;
+
+    fn config_with_grinding() -> PcsConfig {
+        PcsConfig {
+            pow_bits: 10,
+            fri_config: FriConfig::new(0, 1, 3),
+            grinding: vec![
+                Grinding {
+                    point: GrindingPoint::BeforeOodsPoint,
+                    pow_bits: 4,
+                },
+                Grinding {
+                    point: GrindingPoint::FriLayer(0),
+                    pow_bits: 3,
+                },
+                Grinding {
+                    point: GrindingPoint::BeforeOodsPoint,
+                    pow_bits: 5,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_security_bits() {
+        let config = PcsConfig {
+            pow_bits: 42,
+            fri_config: FriConfig::new(10, 10, 70),
+            grinding: vec![],
+        };
+        // 10 * 70 + 42 = 742
+        assert!(config.security_bits() == 742);
+        // 1 * 3 + 10 + 4 + 3 + 5 = 25
+        assert_eq!(config_with_grinding().security_bits(), 25);
+    }
+
+    #[test]
+    fn test_security_bits_per_round() {
+        let mut config = config_with_grinding();
+        config.grinding.push(Grinding {
+            point: GrindingPoint::FriLayer(2),
+            pow_bits: 6,
+        });
+
+        assert_eq!(
+            config.security_bits_per_round(),
+            RoundSecurityBits {
+                before_oods_point: 9,
+                fri_layers: vec![3, 0, 6],
+                queries: 13,
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_grinding_in_order() {
+        let mut commitment_scheme =
+            CommitmentSchemeVerifier::<Blake2sMerkleChannel>::new(config_with_grinding());
+        let mut channel = Blake2sChannel::default();
+        let first_nonce = CpuBackend::grind(&channel, 4);
+        let mut grinding_channel = channel.clone();
+        grinding_channel.mix_u64(first_nonce);
+        let second_nonce = CpuBackend::grind(&grinding_channel, 5);
+        let nonces = [first_nonce, 0, second_nonce];
+
+        commitment_scheme
+            .verify_grinding(GrindingPoint::BeforeOodsPoint, &nonces, &mut channel)
+            .unwrap();
+        grinding_channel.mix_u64(second_nonce);
+        assert_eq!(channel.digest(), grinding_channel.digest());
+    }
+
+    #[test]
+    fn test_verify_grinding_rejects_invalid_nonces() {
+        let mut commitment_scheme =
+            CommitmentSchemeVerifier::<Blake2sMerkleChannel>::new(config_with_grinding());
+        let channel = Blake2sChannel::default();
+        let invalid_nonce = (0..)
+            .find(|&nonce| !verify_pow(&mut channel.clone(), nonce, 4))
+            .unwrap();
+
+        assert!(matches!(
+            commitment_scheme.verify_grinding(
+                GrindingPoint::BeforeOodsPoint,
+                &[0, 0],
+                &mut channel.clone()
+            ),
+            Err(VerificationError::InvalidStructure(_))
+        ));
+        assert!(matches!(
+            commitment_scheme.verify_grinding(
+                GrindingPoint::BeforeOodsPoint,
+                &[invalid_nonce, 0, 0],
+                &mut channel.clone()
+            ),
+            Err(VerificationError::ProofOfWork)
+        ));
+    }
+
+    /// A FRI proof with an inner layer, without decommitments.
+    fn fri_proof() -> FriProof<Blake2sMerkleHasher> {
+        let layer_proof = |commitment| FriLayerProof::<Blake2sMerkleHasher> {
+            fri_witness: vec![],
+            decommitment: MerkleDecommitment {
+                hash_witness: vec![],
+                column_witness: vec![],
+            },
+            commitment,
+        };
+        FriProof {
+            first_layer: layer_proof(Blake2sHash([1; 32])),
+            inner_layers: vec![layer_proof(Blake2sHash([2; 32]))],
+            last_layer_poly: LinePoly::new(vec![SecureField::from_u32_unchecked(1, 2, 3, 4)]),
+        }
+    }
+
+    #[test]
+    fn test_verify_opening_requires_grinding_before_oods_point() {
+        let commitment_scheme =
+            CommitmentSchemeVerifier::<Blake2sMerkleChannel>::new(config_with_grinding());
+        let proof = PcsOpeningProof {
+            decommitments: TreeVec(vec![]),
+            queried_values: TreeVec(vec![]),
+            proof_of_work: 0,
+            grinding_nonces: vec![0; 3],
+            fri_proof: fri_proof(),
+        };
+
+        assert!(matches!(
+            commitment_scheme.verify_opening(
+                TreeVec(vec![]),
+                TreeVec(vec![]),
+                proof,
+                &mut Blake2sChannel::default()
+            ),
+            Err(VerificationError::ProofOfWork)
+        ));
+    }
+
+    #[test]
+    fn test_fri_layer_grinding() {
+        let proof = fri_proof();
+        let mut first_layer_channel = Blake2sChannel::default();
+        Blake2sMerkleChannel::mix_root(&mut first_layer_channel, Blake2sHash([1; 32]));
+        let nonce = CpuBackend::grind(&first_layer_channel, 6);
+        let commit = |grinding: Vec<FriLayerGrinding>| {
+            FriVerifier::<Blake2sMerkleChannel>::commit_with_grinding(
+                &mut Blake2sChannel::default(),
+                FriConfig::new(0, 1, 3),
+                proof.clone(),
+                vec![CirclePolyDegreeBound::new(2)],
+                &grinding,
+            )
+            .err()
+        };
+        let grinding = |layer, nonce| FriLayerGrinding {
+            layer,
+            pow_bits: 6,
+            nonce,
+        };
+
+        assert_eq!(commit(vec![grinding(0, nonce)]), None);
+        assert_eq!(
+            commit(vec![grinding(1, nonce)]),
+            Some(FriVerificationError::LayerProofOfWorkInvalid { layer: 1 })
+        );
+        assert_eq!(
+            commit(vec![grinding(0, nonce), grinding(2, nonce)]),
+            Some(FriVerificationError::LayerProofOfWorkInvalid { layer: 2 })
+        );
+    }
+}
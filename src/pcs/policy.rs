@@ -0,0 +1,197 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+use thiserror_no_std::Error;
+
+use super::PcsConfig;
+use crate::vcs::ops::MerkleHasher;
+
+/// The minimal security requirements a verifier is willing to accept.
+///
+/// A [PcsConfig] is checked against the policy before any verification work is done, so proofs
+/// produced under a weaker configuration are rejected even if they are otherwise valid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityPolicy {
+    /// Minimal value of [PcsConfig::security_bits].
+    pub min_security_bits: u32,
+    /// Minimal number of proof of work bits before the FRI queries are sampled, see
+    /// [PcsConfig::pow_bits]. Grinding at other points doesn't count towards it.
+    pub min_pow_bits: u32,
+    /// Names of the Merkle hashers (see [MerkleHasher::NAME]) that may be used for commitments.
+    pub allowed_hashers: Vec<String>,
+    /// Maximal log degree bound of the last FRI layer.
+    pub max_log_last_layer_degree_bound: u32,
+}
+
+impl SecurityPolicy {
+    pub fn new(
+        min_security_bits: u32,
+        min_pow_bits: u32,
+        allowed_hashers: &[&str],
+        max_log_last_layer_degree_bound: u32,
+    ) -> Self {
+        Self {
+            min_security_bits,
+            min_pow_bits,
+            allowed_hashers: allowed_hashers.iter().map(|h| h.to_string()).collect(),
+            max_log_last_layer_degree_bound,
+        }
+    }
+
+    /// Checks that `config`, used with the hasher `H`, satisfies the policy.
+    ///
+    /// Rules are checked in declaration order and the first violated one is returned.
+    pub fn check<H: MerkleHasher>(&self, config: &PcsConfig) -> Result<(), PolicyViolation> {
+        let security_bits = config.security_bits();
+        if security_bits < self.min_security_bits {
+            return Err(PolicyViolation::InsufficientSecurityBits {
+                required: self.min_security_bits,
+                actual: security_bits,
+            });
+        }
+        if config.pow_bits < self.min_pow_bits {
+            return Err(PolicyViolation::InsufficientPowBits {
+                required: self.min_pow_bits,
+                actual: config.pow_bits,
+            });
+        }
+        if H::NAME.is_empty() || !self.allowed_hashers.iter().any(|name| name == H::NAME) {
+            return Err(PolicyViolation::HasherNotAllowed {
+                hasher: H::NAME.to_string(),
+            });
+        }
+        let log_last_layer_degree_bound = config.fri_config.log_last_layer_degree_bound;
+        if log_last_layer_degree_bound > self.max_log_last_layer_degree_bound {
+            return Err(PolicyViolation::LastLayerDegreeTooLarge {
+                max: self.max_log_last_layer_degree_bound,
+                actual: log_last_layer_degree_bound,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The [SecurityPolicy] rule that a configuration violates.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum PolicyViolation {
+    #[error("security bits {actual} are below the required {required}")]
+    InsufficientSecurityBits { required: u32, actual: u32 },
+    #[error("proof of work bits {actual} are below the required {required}")]
+    InsufficientPowBits { required: u32, actual: u32 },
+    #[error("hasher {hasher} is not allowed")]
+    HasherNotAllowed { hasher: String },
+    #[error("last layer log degree bound {actual} exceeds the maximum {max}")]
+    LastLayerDegreeTooLarge { max: u32, actual: u32 },
+}
+
+impl PolicyViolation {
+    /// A stable numeric code identifying the violated rule. Codes are never reused or reassigned.
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::InsufficientSecurityBits { .. } => 300,
+            Self::InsufficientPowBits { .. } => 301,
+            Self::HasherNotAllowed { .. } => 302,
+            Self::LastLayerDegreeTooLarge { .. } => 303,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{PolicyViolation, SecurityPolicy};
+    use crate::fields::m31::BaseField;
+    use crate::fri::FriConfig;
+    use crate::pcs::{Grinding, GrindingPoint, PcsConfig};
+    use crate::vcs::blake2_hash::Blake2sHash;
+    use crate::vcs::blake2_merkle::Blake2sMerkleHasher;
+    use crate::vcs::ops::MerkleHasher;
+
+    /// A hasher that keeps the default name.
+    #[derive(Debug, Default, Clone)]
+    struct UnnamedHasher;
+
+    impl MerkleHasher for UnnamedHasher {
+        type Hash = Blake2sHash;
+
+        fn hash_node(
+            children_hashes: Option<(Self::Hash, Self::Hash)>,
+            column_values: &[BaseField],
+        ) -> Self::Hash {
+            Blake2sMerkleHasher::hash_node(children_hashes, column_values)
+        }
+    }
+
+    fn config(pow_bits: u32, log_last_layer_degree_bound: u32, n_queries: usize) -> PcsConfig {
+        PcsConfig {
+            pow_bits,
+            fri_config: FriConfig::new(log_last_layer_degree_bound, 1, n_queries),
+            grinding: vec![],
+        }
+    }
+
+    #[test]
+    fn test_policy_accepts_config() {
+        let policy = SecurityPolicy::new(20, 10, &["blake2s"], 2);
+
+        assert_eq!(
+            policy.check::<Blake2sMerkleHasher>(&config(10, 2, 10)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_policy_counts_grinding() {
+        let policy = SecurityPolicy::new(20, 10, &["blake2s"], 2);
+        let mut config = config(10, 0, 5);
+        config.grinding.push(Grinding {
+            point: GrindingPoint::BeforeOodsPoint,
+            pow_bits: 5,
+        });
+
+        assert_eq!(policy.check::<Blake2sMerkleHasher>(&config), Ok(()));
+    }
+
+    #[test]
+    fn test_policy_violations() {
+        let policy = SecurityPolicy::new(20, 10, &["blake2s"], 2);
+
+        assert_eq!(
+            policy.check::<Blake2sMerkleHasher>(&config(10, 0, 5)),
+            Err(PolicyViolation::InsufficientSecurityBits {
+                required: 20,
+                actual: 15
+            })
+        );
+        assert_eq!(
+            policy.check::<Blake2sMerkleHasher>(&config(5, 0, 30)),
+            Err(PolicyViolation::InsufficientPowBits {
+                required: 10,
+                actual: 5
+            })
+        );
+        assert_eq!(
+            policy.check::<Blake2sMerkleHasher>(&config(10, 3, 10)),
+            Err(PolicyViolation::LastLayerDegreeTooLarge { max: 2, actual: 3 })
+        );
+        assert_eq!(
+            SecurityPolicy::new(20, 10, &["poseidon252"], 2)
+                .check::<Blake2sMerkleHasher>(&config(10, 0, 10)),
+            Err(PolicyViolation::HasherNotAllowed {
+                hasher: "blake2s".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_policy_rejects_unnamed_hasher() {
+        let policy = SecurityPolicy::new(20, 10, &["blake2s", ""], 2);
+
+        assert_eq!(
+            policy.check::<UnnamedHasher>(&config(10, 0, 10)),
+            Err(PolicyViolation::HasherNotAllowed { hasher: "".into() })
+        );
+    }
+}
@@ -156,6 +156,50 @@ pub struct CommitmentSchemeProof<H: MerkleHasher> {
     pub decommitments: TreeVec<MerkleDecommitment<H>>,
     pub queried_values: TreeVec<Vec<BaseField>>,
     pub proof_of_work: u64,
+    /// Nonces of the proofs of work in [PcsConfig::grinding], in the same order.
+    #[serde(default)]
+    pub grinding_nonces: Vec<u64>,
+    pub fri_proof: FriProof<H>,
+}
+
+impl<H: MerkleHasher> CommitmentSchemeProof<H> {
+    /// Splits the proof into the claimed sampled values and the proof of their opening.
+    pub fn into_opening(self) -> (TreeVec<ColumnVec<Vec<SecureField>>>, PcsOpeningProof<H>) {
+        let Self {
+            config: _,
+            commitments: _,
+            sampled_values,
+            decommitments,
+            queried_values,
+            proof_of_work,
+            grinding_nonces,
+            fri_proof,
+        } = self;
+        (
+            sampled_values,
+            PcsOpeningProof {
+                decommitments,
+                queried_values,
+                proof_of_work,
+                grinding_nonces,
+                fri_proof,
+            },
+        )
+    }
+}
+
+/// A proof that committed columns open to claimed values at a set of points.
+/// See [`CommitmentSchemeVerifier::verify_opening()`].
+///
+/// [`CommitmentSchemeVerifier::verify_opening()`]: super::CommitmentSchemeVerifier::verify_opening
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PcsOpeningProof<H: MerkleHasher> {
+    pub decommitments: TreeVec<MerkleDecommitment<H>>,
+    pub queried_values: TreeVec<Vec<BaseField>>,
+    pub proof_of_work: u64,
+    /// See [CommitmentSchemeProof::grinding_nonces].
+    #[serde(default)]
+    pub grinding_nonces: Vec<u64>,
     pub fri_proof: FriProof<H>,
 }
 
@@ -1,23 +1,30 @@
 use alloc::collections::BTreeMap;
 use core::cmp::Reverse;
-// use core::iter::zip;
+use core::iter::zip;
 
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use itertools::{izip, multiunzip, Itertools};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 // use tracing::{span, Level};
 
 use super::TreeVec;
-use crate::backend::cpu::quotients::{accumulate_row_quotients, quotient_constants};
+use crate::backend::cpu::quotients::{
+    accumulate_row_quotients_with_inverses, denominator, RowQuotientConstants,
+};
 use crate::circle::CirclePoint;
+use crate::fields::cm31::CM31;
 use crate::fields::m31::BaseField;
 use crate::fields::qm31::SecureField;
+use crate::fields::FieldExpOps;
 use crate::poly::circle::{
     CanonicCoset, CircleDomain, CircleEvaluation, PolyOps, SecureEvaluation,
 };
 use crate::poly::BitReversedOrder;
 use crate::utils::bit_reverse_index;
-use crate::ColumnVec;
 use crate::VerificationError;
+use crate::{parallel_iter, ColumnVec};
 
 pub trait QuotientOps: PolyOps {
     /// Accumulates the quotients of the columns at the given domain.
@@ -101,6 +108,12 @@ pub struct PointSample {
 //         .collect()
 // }
 
+/// Computes the FRI quotient evaluations at the queried positions, for each column log size in
+/// descending order.
+///
+/// The quotient denominators of every query and sample batch, across all log sizes, are inverted
+/// with a single batch inversion. With the `parallel` feature, the log sizes are then answered on
+/// all threads.
 pub fn fri_answers(
     column_log_sizes: TreeVec<Vec<u32>>,
     samples: TreeVec<Vec<Vec<PointSample>>>,
@@ -109,26 +122,56 @@ pub fn fri_answers(
     queried_values: TreeVec<Vec<BaseField>>,
     n_columns_per_log_size: TreeVec<&BTreeMap<u32, usize>>,
 ) -> Result<ColumnVec<Vec<SecureField>>, VerificationError> {
-    let mut queried_values = queried_values.map(|values| values.into_iter());
-
-    izip!(column_log_sizes.flatten(), samples.flatten().iter())
+    let log_size_queries = izip!(column_log_sizes.flatten(), samples.flatten().iter())
         .sorted_by_key(|(log_size, ..)| Reverse(*log_size))
         .group_by(|(log_size, ..)| *log_size)
         .into_iter()
         .map(|(log_size, tuples)| {
             let (_, samples): (Vec<_>, Vec<_>) = multiunzip(tuples);
-            fri_answers_for_log_size(
+            LogSizeQueries::new(
                 log_size,
                 &samples,
                 random_coeff,
                 &query_positions_per_log_size[&log_size],
-                &mut queried_values,
-                n_columns_per_log_size
-                    .as_ref()
-                    .map(|colums_log_sizes| *colums_log_sizes.get(&log_size).unwrap_or(&0)),
             )
         })
-        .collect()
+        .collect_vec();
+
+    let denominators = log_size_queries
+        .iter()
+        .flat_map(LogSizeQueries::denominators)
+        .collect_vec();
+    let denominator_inverses = CM31::batch_inverse(&denominators);
+
+    // Split the inverses and the queried values of each tree by log size.
+    let mut denominator_inverses = denominator_inverses.as_slice();
+    let mut queried_values = queried_values.as_ref().map(|values| values.as_slice());
+    let mut log_size_inputs = Vec::with_capacity(log_size_queries.len());
+    for queries in &log_size_queries {
+        let (log_size_inverses, rest) = denominator_inverses.split_at(queries.n_denominators());
+        denominator_inverses = rest;
+        let n_columns = n_columns_per_log_size
+            .as_ref()
+            .map(|columns_per_log_size| *columns_per_log_size.get(&queries.log_size).unwrap_or(&0));
+        let mut log_size_values = Vec::with_capacity(queried_values.len());
+        for (values, &n_columns) in zip(queried_values.iter_mut(), n_columns.iter()) {
+            let n_values = queries.domain_points.len() * n_columns;
+            if values.len() < n_values {
+                return Err(not_enough_queried_values());
+            }
+            let (tree_values, rest) = values.split_at(n_values);
+            *values = rest;
+            log_size_values.push(tree_values);
+        }
+        log_size_inputs.push((log_size_inverses, TreeVec::new(log_size_values), n_columns));
+    }
+
+    Ok(parallel_iter!(log_size_queries)
+        .zip(log_size_inputs)
+        .map(|(queries, (inverses, values, n_columns))| {
+            queries.answers(inverses, values, n_columns)
+        })
+        .collect())
 }
 
 pub fn fri_answers_for_log_size(
@@ -139,30 +182,104 @@ pub fn fri_answers_for_log_size(
     queried_values: &mut TreeVec<impl Iterator<Item = BaseField>>,
     n_columns: TreeVec<usize>,
 ) -> Result<Vec<SecureField>, VerificationError> {
-    let sample_batches = ColumnSampleBatch::new_vec(samples);
-    // TODO(ilya): Is it ok to use the same `random_coeff` for all log sizes.
-    let quotient_constants = quotient_constants(&sample_batches, random_coeff);
-    let commitment_domain = CanonicCoset::new(log_size).circle_domain();
-
-    let mut quotient_evals_at_queries = Vec::new();
-    for &query_position in query_positions {
-        let domain_point = commitment_domain.at(bit_reverse_index(query_position, log_size));
-
-        let queried_values_at_row = queried_values
-            .as_mut()
-            .zip_eq(n_columns.as_ref())
-            .map(|(queried_values, n_columns)| queried_values.take(*n_columns).collect())
-            .flatten();
-
-        quotient_evals_at_queries.push(accumulate_row_quotients(
-            &sample_batches,
-            &queried_values_at_row,
-            &quotient_constants,
-            domain_point,
-        ));
+    let queries = LogSizeQueries::new(log_size, samples, random_coeff, query_positions);
+    let denominators = queries.denominators().collect_vec();
+    let values = zip(queried_values.iter_mut(), n_columns.iter())
+        .map(|(values, &n_columns)| {
+            let n_values = query_positions.len() * n_columns;
+            let values = values.take(n_values).collect_vec();
+            if values.len() < n_values {
+                return Err(not_enough_queried_values());
+            }
+            Ok(values)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(queries.answers(
+        &CM31::batch_inverse(&denominators),
+        TreeVec::new(values.iter().map(Vec::as_slice).collect()),
+        n_columns,
+    ))
+}
+
+fn not_enough_queried_values() -> VerificationError {
+    VerificationError::InvalidStructure("not enough queried values".to_string())
+}
+
+/// The queries to the columns of a single log size, with the constants needed to answer them.
+struct LogSizeQueries {
+    log_size: u32,
+    sample_batches: Vec<ColumnSampleBatch>,
+    quotient_constants: RowQuotientConstants,
+    domain_points: Vec<CirclePoint<BaseField>>,
+}
+
+impl LogSizeQueries {
+    fn new(
+        log_size: u32,
+        samples: &[&Vec<PointSample>],
+        random_coeff: SecureField,
+        query_positions: &[usize],
+    ) -> Self {
+        let sample_batches = ColumnSampleBatch::new_vec(samples);
+        // TODO(ilya): Is it ok to use the same `random_coeff` for all log sizes.
+        let quotient_constants = RowQuotientConstants::new(&sample_batches, random_coeff);
+        let commitment_domain = CanonicCoset::new(log_size).circle_domain();
+        let domain_points = query_positions
+            .iter()
+            .map(|&position| commitment_domain.at(bit_reverse_index(position, log_size)))
+            .collect();
+
+        Self {
+            log_size,
+            sample_batches,
+            quotient_constants,
+            domain_points,
+        }
+    }
+
+    const fn n_denominators(&self) -> usize {
+        self.domain_points.len() * self.sample_batches.len()
     }
 
-    Ok(quotient_evals_at_queries)
+    /// The quotient denominators, query-major.
+    fn denominators(&self) -> impl Iterator<Item = CM31> + '_ {
+        self.domain_points.iter().flat_map(|&domain_point| {
+            self.sample_batches
+                .iter()
+                .map(move |sample_batch| denominator(sample_batch.point, domain_point))
+        })
+    }
+
+    /// Answers the queries, given the queried values of each tree in query-major order.
+    fn answers(
+        &self,
+        denominator_inverses: &[CM31],
+        queried_values: TreeVec<&[BaseField]>,
+        n_columns: TreeVec<usize>,
+    ) -> Vec<SecureField> {
+        let n_batches = self.sample_batches.len();
+        let mut queried_values_at_row = Vec::with_capacity(n_columns.iter().sum());
+
+        self.domain_points
+            .iter()
+            .enumerate()
+            .map(|(query_index, domain_point)| {
+                queried_values_at_row.clear();
+                for (queried_values, &n_columns) in zip(queried_values.iter(), n_columns.iter()) {
+                    queried_values_at_row.extend_from_slice(
+                        &queried_values[query_index * n_columns..(query_index + 1) * n_columns],
+                    );
+                }
+
+                accumulate_row_quotients_with_inverses(
+                    &self.quotient_constants,
+                    &queried_values_at_row,
+                    domain_point.y,
+                    &denominator_inverses[query_index * n_batches..(query_index + 1) * n_batches],
+                )
+            })
+            .collect()
+    }
 }
 
 // #[cfg(test)]
@@ -197,3 +314,162 @@ pub fn fri_answers_for_log_size(
 //         assert!(quot_poly_base_field.is_in_fri_space(LOG_SIZE));
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use itertools::Itertools;
+
+    use super::{fri_answers, ColumnSampleBatch, PointSample};
+    use crate::backend::cpu::quotients::{accumulate_row_quotients, quotient_constants};
+    use crate::channel::{Blake2sChannel, Channel};
+    use crate::circle::CirclePoint;
+    use crate::fields::m31::BaseField;
+    use crate::fields::qm31::SecureField;
+    use crate::pcs::TreeVec;
+    use crate::poly::circle::CanonicCoset;
+    use crate::utils::bit_reverse_index;
+    use crate::VerificationError;
+
+    /// Answers the queries row by row, inverting the denominators of each row separately.
+    fn fri_answers_by_row(
+        column_log_sizes: &TreeVec<Vec<u32>>,
+        samples: &TreeVec<Vec<Vec<PointSample>>>,
+        random_coeff: SecureField,
+        query_positions_per_log_size: &BTreeMap<u32, Vec<usize>>,
+        queried_values: &TreeVec<Vec<BaseField>>,
+    ) -> Vec<Vec<SecureField>> {
+        let mut queried_values = queried_values.as_ref().map(|values| values.iter().copied());
+        query_positions_per_log_size
+            .iter()
+            .rev()
+            .map(|(&log_size, query_positions)| {
+                let samples = column_log_sizes
+                    .iter()
+                    .flatten()
+                    .zip(samples.iter().flatten())
+                    .filter(|(&column_log_size, _)| column_log_size == log_size)
+                    .map(|(_, samples)| samples)
+                    .collect_vec();
+                let sample_batches = ColumnSampleBatch::new_vec(&samples);
+                let quotient_constants = quotient_constants(&sample_batches, random_coeff);
+                let domain = CanonicCoset::new(log_size).circle_domain();
+                query_positions
+                    .iter()
+                    .map(|&position| {
+                        let row = column_log_sizes
+                            .iter()
+                            .zip(queried_values.iter_mut())
+                            .flat_map(|(log_sizes, values)| {
+                                let n_columns = log_sizes.iter().filter(|&&s| s == log_size);
+                                values.take(n_columns.count()).collect_vec()
+                            })
+                            .collect_vec();
+                        accumulate_row_quotients(
+                            &sample_batches,
+                            &row,
+                            &quotient_constants,
+                            domain.at(bit_reverse_index(position, log_size)),
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fri_answers_match_row_by_row_evaluation() {
+        let channel = &mut Blake2sChannel::default();
+        let random_coeff = channel.draw_felt();
+        let points: [CirclePoint<SecureField>; 3] =
+            core::array::from_fn(|_| CirclePoint::get_random_point(channel));
+        let column_log_sizes = TreeVec::new(vec![vec![5, 7, 5], vec![7, 6, 7, 5]]);
+        let samples = column_log_sizes.as_ref().map(|log_sizes| {
+            log_sizes
+                .iter()
+                .enumerate()
+                .map(|(i, &log_size)| {
+                    points[..1 + (i + log_size as usize) % 3]
+                        .iter()
+                        .map(|&point| PointSample {
+                            point,
+                            value: SecureField::from_u32_unchecked(i as u32, log_size, 3, 4),
+                        })
+                        .collect_vec()
+                })
+                .collect_vec()
+        });
+        let query_positions_per_log_size = BTreeMap::from([
+            (5, vec![0, 3, 17, 31]),
+            (6, vec![1, 6, 34, 35, 63]),
+            (7, vec![2, 12, 13, 69, 70, 127]),
+        ]);
+        let queried_values = column_log_sizes.as_ref().map(|log_sizes| {
+            query_positions_per_log_size
+                .iter()
+                .rev()
+                .flat_map(|(log_size, positions)| {
+                    let n_columns = log_sizes.iter().filter(|&s| s == log_size).count();
+                    (0..positions.len() * n_columns).map(|i| {
+                        BaseField::from_u32_unchecked((i * 31 + *log_size as usize) as u32)
+                    })
+                })
+                .collect_vec()
+        });
+        let expected = fri_answers_by_row(
+            &column_log_sizes,
+            &samples,
+            random_coeff,
+            &query_positions_per_log_size,
+            &queried_values,
+        );
+        let n_columns_per_log_size = column_log_sizes.as_ref().map(|log_sizes| {
+            let mut n_columns = BTreeMap::new();
+            for &log_size in log_sizes {
+                *n_columns.entry(log_size).or_insert(0) += 1;
+            }
+            n_columns
+        });
+
+        let answers = fri_answers(
+            column_log_sizes,
+            samples,
+            random_coeff,
+            &query_positions_per_log_size,
+            queried_values,
+            n_columns_per_log_size.as_ref(),
+        )
+        .unwrap();
+
+        assert_eq!(answers, expected);
+    }
+
+    #[test]
+    fn test_fri_answers_rejects_missing_queried_values() {
+        let channel = &mut Blake2sChannel::default();
+        let random_coeff = channel.draw_felt();
+        let point = CirclePoint::get_random_point(channel);
+        let samples = TreeVec::new(vec![vec![vec![PointSample {
+            point,
+            value: SecureField::from_u32_unchecked(1, 2, 3, 4),
+        }]]]);
+        let n_columns_per_log_size = BTreeMap::from([(5, 1)]);
+
+        let result = fri_answers(
+            TreeVec::new(vec![vec![5]]),
+            samples,
+            random_coeff,
+            &BTreeMap::from([(5, vec![3, 17])]),
+            TreeVec::new(vec![vec![BaseField::from_u32_unchecked(7)]]),
+            TreeVec::new(vec![&n_columns_per_log_size]),
+        );
+
+        assert!(matches!(
+            result,
+            Err(VerificationError::InvalidStructure(_))
+        ));
+    }
+}
@@ -1,25 +1,33 @@
 use core::iter::zip;
 
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use itertools::Itertools;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 use super::super::circle::CirclePoint;
 use super::super::fields::qm31::SecureField;
-use super::super::fri::{CirclePolyDegreeBound, FriVerifier};
+use super::super::fri::{CirclePolyDegreeBound, FriLayerGrinding, FriVerifier};
+use super::prover::PcsOpeningProof;
 use super::quotients::{fri_answers, PointSample};
 use super::utils::TreeVec;
-use super::{CommitmentSchemeProof, PcsConfig};
-use crate::channel::{Channel, MerkleChannel};
+use super::{CommitmentSchemeProof, GrindingPoint, PcsConfig, SecurityPolicy};
+use crate::channel::{ChallengeSource, MerkleChannel};
+use crate::proof_of_work::verify_pow;
 use crate::vcs::ops::MerkleHasher;
 use crate::vcs::verifier::MerkleVerifier;
-use crate::ColumnVec;
 use crate::VerificationError;
+use crate::{parallel_iter, ColumnVec};
 
 /// The verifier side of a FRI polynomial commitment scheme. See [super].
 #[derive(Default)]
 pub struct CommitmentSchemeVerifier<MC: MerkleChannel> {
     pub trees: TreeVec<MerkleVerifier<MC::H>>,
     pub config: PcsConfig,
+    pub policy: Option<SecurityPolicy>,
+    /// Whether [Self::verify_grinding] verified the proofs of work before the OODS point.
+    before_oods_point_verified: bool,
 }
 
 impl<MC: MerkleChannel> CommitmentSchemeVerifier<MC> {
@@ -27,9 +35,29 @@ impl<MC: MerkleChannel> CommitmentSchemeVerifier<MC> {
         Self {
             trees: TreeVec::default(),
             config,
+            policy: None,
+            before_oods_point_verified: false,
         }
     }
 
+    /// Creates a verifier that rejects proofs whose config does not satisfy `policy`.
+    pub fn new_with_policy(config: PcsConfig, policy: SecurityPolicy) -> Self {
+        Self {
+            trees: TreeVec::default(),
+            config,
+            policy: Some(policy),
+            before_oods_point_verified: false,
+        }
+    }
+
+    /// Checks the config and hasher against the security policy, if one is set.
+    pub fn check_policy(&self) -> Result<(), VerificationError> {
+        if let Some(policy) = &self.policy {
+            policy.check::<MC::H>(&self.config)?;
+        }
+        Ok(())
+    }
+
     /// A [TreeVec<ColumnVec>] of the log sizes of each column in each commitment tree.
     fn column_log_sizes(&self) -> TreeVec<ColumnVec<u32>> {
         self.trees
@@ -53,13 +81,63 @@ impl<MC: MerkleChannel> CommitmentSchemeVerifier<MC> {
         self.trees.push(verifier);
     }
 
+    /// Verifies a [CommitmentSchemeProof] on values sampled at `sampled_points`.
+    ///
+    /// This is [Self::verify_opening] with the sampled values taken from the proof.
     pub fn verify_values(
         &self,
         sampled_points: TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
         proof: CommitmentSchemeProof<MC::H>,
         channel: &mut MC::C,
     ) -> Result<(), VerificationError> {
-        channel.mix_felts(&proof.sampled_values.clone().flatten_cols());
+        let (sampled_values, opening_proof) = proof.into_opening();
+        self.verify_opening(sampled_points, sampled_values, opening_proof, channel)
+    }
+
+    /// Verifies that the committed columns evaluate to `sampled_values` at `sampled_points`.
+    ///
+    /// Both `sampled_points` and `sampled_values` hold, for each tree committed with
+    /// [Self::commit] and for each column in it, the list of points at which the column is opened
+    /// and the claimed values at those points. A column may be opened at any number of points,
+    /// none of which may lie on its commitment domain.
+    ///
+    /// The sampled values are mixed into `channel` before any randomness is drawn, so the caller
+    /// only needs to keep its own transcript in sync up to the points it sampled. Proofs of work at
+    /// [GrindingPoint::BeforeOodsPoint] must already be verified with [Self::verify_grinding].
+    pub fn verify_opening(
+        &self,
+        sampled_points: TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
+        sampled_values: TreeVec<ColumnVec<Vec<SecureField>>>,
+        proof: PcsOpeningProof<MC::H>,
+        channel: &mut MC::C,
+    ) -> Result<(), VerificationError> {
+        let result = self.verify_opening_proof(sampled_points, sampled_values, proof, channel);
+        // A malformed challenge is replaced by zeroes, which may fail a later check first.
+        channel
+            .malformed_challenge()
+            .map_or(result, |error| Err(error.into()))
+    }
+
+    fn verify_opening_proof(
+        &self,
+        sampled_points: TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
+        sampled_values: TreeVec<ColumnVec<Vec<SecureField>>>,
+        proof: PcsOpeningProof<MC::H>,
+        channel: &mut MC::C,
+    ) -> Result<(), VerificationError> {
+        self.check_policy()?;
+        self.validate_opening_structure(&sampled_points, &sampled_values, &proof)?;
+        // The points are already drawn, so it's too late to verify the grinding before them.
+        if !self.before_oods_point_verified
+            && self
+                .config
+                .grinding
+                .iter()
+                .any(|grinding| grinding.point == GrindingPoint::BeforeOodsPoint)
+        {
+            return Err(VerificationError::ProofOfWork);
+        }
+        channel.mix_felts(&sampled_values.clone().flatten_cols());
         let random_coeff = channel.draw_felt();
         let bounds = self
             .column_log_sizes()
@@ -72,37 +150,54 @@ impl<MC: MerkleChannel> CommitmentSchemeVerifier<MC> {
                 CirclePolyDegreeBound::new(log_size - self.config.fri_config.log_blowup_factor)
             })
             .collect_vec();
+        let fri_grinding = zip(&self.config.grinding, &proof.grinding_nonces)
+            .filter_map(|(grinding, &nonce)| match grinding.point {
+                GrindingPoint::FriLayer(layer) => Some(FriLayerGrinding {
+                    layer,
+                    pow_bits: grinding.pow_bits,
+                    nonce,
+                }),
+                GrindingPoint::BeforeOodsPoint => None,
+            })
+            .collect_vec();
         // FRI commitment phase on OODS quotients.
-        let mut fri_verifier =
-            FriVerifier::<MC>::commit(channel, self.config.fri_config, proof.fri_proof, bounds)?;
+        let mut fri_verifier = FriVerifier::<MC>::commit_with_grinding(
+            channel,
+            self.config.fri_config,
+            proof.fri_proof,
+            bounds,
+            &fri_grinding,
+        )?;
         // Verify proof of work.
-        channel.mix_u64(proof.proof_of_work);
-        if channel.trailing_zeros() < self.config.pow_bits {
+        if !verify_pow(channel, proof.proof_of_work, self.config.pow_bits) {
             return Err(VerificationError::ProofOfWork);
         }
 
         // Get FRI query positions.
         let query_positions_per_log_size = fri_verifier.sample_query_positions(channel);
-        // Verify merkle decommitments.
-        self.trees
-            .as_ref()
-            .zip_eq(proof.decommitments)
-            .zip_eq(proof.queried_values.clone())
+        // Verify merkle decommitments. Errors are reported for the first failing tree, also when
+        // the trees are verified in parallel.
+        parallel_iter!(&self.trees.0)
+            .zip(proof.decommitments.0)
+            .zip(proof.queried_values.0.clone())
             .map(|((tree, decommitment), queried_values)| {
                 tree.verify(&query_positions_per_log_size, queried_values, decommitment)
             })
-            .0
+            .collect::<Vec<_>>()
             .into_iter()
-            .collect::<Result<(), _>>()?;
+            .enumerate()
+            .try_for_each(|(tree_index, result)| {
+                result.map_err(|error| VerificationError::Merkle { tree_index, error })
+            })?;
 
         // Answer FRI queries.
-        let samples = sampled_points.zip_cols(proof.sampled_values).map_cols(
-            |(sampled_points, sampled_values)| {
-                zip(sampled_points, sampled_values)
+        let samples = sampled_points
+            .zip_cols(sampled_values)
+            .map_cols(|(points, values)| {
+                zip(points, values)
                     .map(|(point, value)| PointSample { point, value })
                     .collect_vec()
-            },
-        );
+            });
 
         let n_columns_per_log_size = self.trees.as_ref().map(|tree| &tree.n_columns_per_log_size);
 
@@ -119,4 +214,74 @@ impl<MC: MerkleChannel> CommitmentSchemeVerifier<MC> {
 
         Ok(())
     }
+
+    /// Verifies the proofs of work of [PcsConfig::grinding] placed at `point`, in order.
+    ///
+    /// `nonces` holds the nonces of all the grinding points, see
+    /// [CommitmentSchemeProof::grinding_nonces]. FRI layer proofs of work are verified by
+    /// [Self::verify_opening], which fails unless the ones before the OODS point were verified
+    /// here.
+    pub fn verify_grinding(
+        &mut self,
+        point: GrindingPoint,
+        nonces: &[u64],
+        channel: &mut MC::C,
+    ) -> Result<(), VerificationError> {
+        self.validate_grinding_structure(nonces)?;
+        for (grinding, &nonce) in zip(&self.config.grinding, nonces) {
+            if grinding.point == point && !verify_pow(channel, nonce, grinding.pow_bits) {
+                return Err(VerificationError::ProofOfWork);
+            }
+        }
+        if point == GrindingPoint::BeforeOodsPoint {
+            self.before_oods_point_verified = true;
+        }
+        Ok(())
+    }
+
+    fn validate_grinding_structure(&self, nonces: &[u64]) -> Result<(), VerificationError> {
+        if nonces.len() != self.config.grinding.len() {
+            return Err(VerificationError::InvalidStructure(
+                "number of grinding nonces does not match the config".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that the opening claims and the proof match the shape of the committed trees.
+    fn validate_opening_structure(
+        &self,
+        sampled_points: &TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
+        sampled_values: &TreeVec<ColumnVec<Vec<SecureField>>>,
+        proof: &PcsOpeningProof<MC::H>,
+    ) -> Result<(), VerificationError> {
+        self.validate_grinding_structure(&proof.grinding_nonces)?;
+        let n_trees = self.trees.len();
+        if sampled_points.len() != n_trees
+            || sampled_values.len() != n_trees
+            || proof.decommitments.len() != n_trees
+            || proof.queried_values.len() != n_trees
+        {
+            return Err(VerificationError::InvalidStructure(
+                "number of trees does not match the commitments".to_string(),
+            ));
+        }
+        for (tree, (points, values)) in zip(
+            self.trees.iter(),
+            zip(sampled_points.iter(), sampled_values.iter()),
+        ) {
+            let n_columns = tree.column_log_sizes.len();
+            if points.len() != n_columns || values.len() != n_columns {
+                return Err(VerificationError::InvalidStructure(
+                    "number of columns does not match the commitment".to_string(),
+                ));
+            }
+            if zip(points, values).any(|(points, values)| points.len() != values.len()) {
+                return Err(VerificationError::InvalidStructure(
+                    "number of sampled values does not match the sampled points".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
 }
@@ -1,7 +1,14 @@
-use crate::channel::Channel;
+use crate::channel::{ChallengeSource, Channel};
 
 pub trait GrindOps<C: Channel> {
     /// Searches for a nonce s.t. mixing it to the channel makes the digest have `pow_bits` leading
     /// zero bits.
     fn grind(channel: &C, pow_bits: u32) -> u64;
 }
+
+/// Mixes `nonce` into `channel` and checks that the digest has at least `pow_bits` trailing zero
+/// bits.
+pub fn verify_pow(channel: &mut impl ChallengeSource, nonce: u64, pow_bits: u32) -> bool {
+    channel.mix_u64(nonce);
+    channel.trailing_zeros() >= pow_bits
+}
@@ -4,7 +4,7 @@ use core::ops::Deref;
 use alloc::vec::Vec;
 use itertools::Itertools;
 
-use super::channel::Channel;
+use super::channel::ChallengeSource;
 
 pub const UPPER_BOUND_QUERY_BYTES: usize = 4;
 
@@ -19,7 +19,11 @@ pub struct Queries {
 
 impl Queries {
     /// Randomizes a set of query indices uniformly over the range [0, 2^`log_query_size`).
-    pub fn generate(channel: &mut impl Channel, log_domain_size: u32, n_queries: usize) -> Self {
+    pub fn generate(
+        channel: &mut impl ChallengeSource,
+        log_domain_size: u32,
+        n_queries: usize,
+    ) -> Self {
         let mut queries = BTreeSet::new();
         let mut query_cnt = 0;
         let max_query = (1 << log_domain_size) - 1;
@@ -1,10 +1,10 @@
 // use alloc::vec::Vec;
-use itertools::Itertools;
-use num_traits::Zero;
+use core::iter::zip;
+
 use serde::{Deserialize, Serialize};
 
 use super::blake2_hash::Blake2sHash;
-use super::blake2s_ref::compress;
+use super::blake2s::compress;
 use super::ops::MerkleHasher;
 use crate::channel::{Blake2sChannel, MerkleChannel};
 use crate::fields::m31::BaseField;
@@ -13,6 +13,7 @@ use crate::fields::m31::BaseField;
 pub struct Blake2sMerkleHasher;
 impl MerkleHasher for Blake2sMerkleHasher {
     type Hash = Blake2sHash;
+    const NAME: &'static str = "blake2s";
 
     fn hash_node(
         children_hashes: Option<(Self::Hash, Self::Hash)>,
@@ -29,32 +30,13 @@ impl MerkleHasher for Blake2sMerkleHasher {
                 0,
             );
         }
-        let rem = 15 - ((column_values.len() + 15) % 16);
-        let padded_values = column_values
-            .iter()
-            .copied()
-            .chain(core::iter::repeat(BaseField::zero()).take(rem));
-        // for chunk in padded_values.array_chunks::<16>() {
-        //     state = compress(
-        //         state,
-        //         unsafe { core::mem::transmute::<[BaseField; 16], [u32; 16]>(chunk) },
-        //         0,
-        //         0,
-        //         0,
-        //         0,
-        //     );
-        // }
-        for chunk in &padded_values.chunks(16) {
-            let chunk = chunk.collect_vec().try_into().unwrap();
-
-            state = compress(
-                state,
-                unsafe { core::mem::transmute::<[BaseField; 16], [u32; 16]>(chunk) },
-                0,
-                0,
-                0,
-                0,
-            );
+        // Values are absorbed in chunks of 16, the last one padded with zeros.
+        for chunk in column_values.chunks(16) {
+            let mut msg_vecs = [0; 16];
+            for (word, value) in zip(&mut msg_vecs, chunk) {
+                *word = value.0;
+            }
+            state = compress(state, msg_vecs, 0, 0, 0, 0);
         }
         state.map(|x| x.to_le_bytes()).as_flattened().into()
     }
@@ -75,95 +57,136 @@ impl MerkleChannel for Blake2sMerkleChannel {
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use num_traits::Zero;
-
-//     use super::Blake2sMerkleChannel;
-//     use crate::core::channel::{Blake2sChannel, MerkleChannel};
-//     use crate::core::fields::m31::BaseField;
-//     use crate::core::vcs::blake2_merkle::{Blake2sHash, Blake2sMerkleHasher};
-//     use crate::core::vcs::test_utils::prepare_merkle;
-//     use crate::core::vcs::verifier::MerkleVerificationError;
-
-//     #[test]
-//     fn test_merkle_success() {
-//         let (queries, decommitment, values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
-
-//         verifier.verify(&queries, values, decommitment).unwrap();
-//     }
-
-//     #[test]
-//     fn test_merkle_invalid_witness() {
-//         let (queries, mut decommitment, values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
-//         decommitment.hash_witness[4] = Blake2sHash::default();
-
-//         assert_eq!(
-//             verifier.verify(&queries, values, decommitment).unwrap_err(),
-//             MerkleVerificationError::RootMismatch
-//         );
-//     }
-
-//     #[test]
-//     fn test_merkle_invalid_value() {
-//         let (queries, decommitment, mut values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
-//         values[6] = BaseField::zero();
-
-//         assert_eq!(
-//             verifier.verify(&queries, values, decommitment).unwrap_err(),
-//             MerkleVerificationError::RootMismatch
-//         );
-//     }
-
-//     #[test]
-//     fn test_merkle_witness_too_short() {
-//         let (queries, mut decommitment, values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
-//         decommitment.hash_witness.pop();
-
-//         assert_eq!(
-//             verifier.verify(&queries, values, decommitment).unwrap_err(),
-//             MerkleVerificationError::WitnessTooShort
-//         );
-//     }
-
-//     #[test]
-//     fn test_merkle_witness_too_long() {
-//         let (queries, mut decommitment, values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
-//         decommitment.hash_witness.push(Blake2sHash::default());
-
-//         assert_eq!(
-//             verifier.verify(&queries, values, decommitment).unwrap_err(),
-//             MerkleVerificationError::WitnessTooLong
-//         );
-//     }
-
-//     #[test]
-//     fn test_merkle_column_values_too_long() {
-//         let (queries, decommitment, mut values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
-//         values.insert(3, BaseField::zero());
-
-//         assert_eq!(
-//             verifier.verify(&queries, values, decommitment).unwrap_err(),
-//             MerkleVerificationError::TooManyQueriedValues
-//         );
-//     }
-
-//     #[test]
-//     fn test_merkle_column_values_too_short() {
-//         let (queries, decommitment, mut values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
-//         values.remove(3);
-
-//         assert_eq!(
-//             verifier.verify(&queries, values, decommitment).unwrap_err(),
-//             MerkleVerificationError::TooFewQueriedValues
-//         );
-//     }
-
-//     #[test]
-//     fn test_merkle_channel() {
-//         let mut channel = Blake2sChannel::default();
-//         let (_queries, _decommitment, _values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
-//         Blake2sMerkleChannel::mix_root(&mut channel, verifier.root);
-//         assert_eq!(channel.channel_time.n_challenges, 1);
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use num_traits::Zero;
+
+    use super::Blake2sMerkleChannel;
+    use crate::channel::{Blake2sChannel, MerkleChannel};
+    use crate::fields::m31::BaseField;
+    use crate::vcs::blake2_merkle::{Blake2sHash, Blake2sMerkleHasher};
+    use crate::vcs::blake2s_ref;
+    use crate::vcs::ops::MerkleHasher;
+    use crate::vcs::test_utils::{prepare_merkle, prepare_random_merkle};
+    use crate::vcs::verifier::MerkleVerificationError;
+
+    #[test]
+    fn test_merkle_success() {
+        let (queries, decommitment, values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
+
+        verifier.verify(&queries, values, decommitment).unwrap();
+    }
+
+    #[test]
+    fn test_hash_node_pads_values() {
+        let values = (0..40).map(BaseField::from).collect_vec();
+
+        for n_values in [0, 1, 15, 16, 17, 40] {
+            let mut padded_values = values[..n_values].iter().map(|v| v.0).collect_vec();
+            padded_values.resize(n_values.div_ceil(16) * 16, 0);
+            let state = padded_values.chunks(16).fold([0; 8], |state, chunk| {
+                blake2s_ref::compress(state, chunk.try_into().unwrap(), 0, 0, 0, 0)
+            });
+            let expected = state.map(u32::to_le_bytes).concat();
+
+            assert_eq!(
+                Blake2sMerkleHasher::hash_node(None, &values[..n_values]).0[..],
+                expected[..]
+            );
+        }
+    }
+
+    #[test]
+    fn test_merkle_random_shapes_success() {
+        for seed in 0..32 {
+            let n_cols = 1 + seed as usize % 12;
+            let min_log_size = seed as u32 % 4;
+            let max_log_size = min_log_size + 1 + seed as u32 % 6;
+            let n_queries = 1 + seed as usize % 5;
+            let (queries, decommitment, values, verifier) =
+                prepare_random_merkle::<Blake2sMerkleHasher>(
+                    seed,
+                    n_cols,
+                    min_log_size..max_log_size,
+                    n_queries,
+                );
+
+            verifier.verify(&queries, values, decommitment).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_merkle_invalid_witness() {
+        let (queries, mut decommitment, values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
+        decommitment.hash_witness[4] = Blake2sHash::default();
+
+        assert!(matches!(
+            verifier.verify(&queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::RootMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_merkle_invalid_value() {
+        let (queries, decommitment, mut values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
+        values[6] = BaseField::zero();
+
+        assert!(matches!(
+            verifier.verify(&queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::RootMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_merkle_witness_too_short() {
+        let (queries, mut decommitment, values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
+        decommitment.hash_witness.pop();
+
+        assert!(matches!(
+            verifier.verify(&queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::WitnessTooShort { .. }
+        ));
+    }
+
+    #[test]
+    fn test_merkle_witness_too_long() {
+        let (queries, mut decommitment, values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
+        decommitment.hash_witness.push(Blake2sHash::default());
+
+        assert_eq!(
+            verifier.verify(&queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::WitnessTooLong
+        );
+    }
+
+    #[test]
+    fn test_merkle_column_values_too_long() {
+        let (queries, decommitment, mut values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
+        values.insert(3, BaseField::zero());
+
+        assert_eq!(
+            verifier.verify(&queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::TooManyQueriedValues
+        );
+    }
+
+    #[test]
+    fn test_merkle_column_values_too_short() {
+        let (queries, decommitment, mut values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
+        values.remove(3);
+
+        assert!(matches!(
+            verifier.verify(&queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::TooFewQueriedValues { .. }
+        ));
+    }
+
+    #[test]
+    fn test_merkle_channel() {
+        let mut channel = Blake2sChannel::default();
+        let (_queries, _decommitment, _values, verifier) = prepare_merkle::<Blake2sMerkleHasher>();
+        Blake2sMerkleChannel::mix_root(&mut channel, verifier.root);
+        assert_eq!(channel.channel_time.n_challenges, 1);
+    }
+}
@@ -0,0 +1,510 @@
+//! An optimized BLAKE2s compression function.
+//!
+//! [compress] is a drop-in replacement for [blake2s_ref::compress](super::blake2s_ref::compress).
+//! The implementation is selected at compile time:
+//! * On x86 with SSSE3, as in any AVX2 build, `sse2` keeps the state in four 128-bit rows and
+//!   runs the four column (then diagonal) mixes of a round in parallel, with the 16 and 8 bit
+//!   rotations done by a single byte shuffle. A single compression only has four independent
+//!   lanes, so wider AVX2 registers are not used.
+//! * On aarch64 with NEON, `neon` uses the same row layout.
+//! * Otherwise, the [scalar] implementation is used. This includes x86 with SSE2 only, where the
+//!   rotations take three instructions and `sse2` was measured to be slower than [scalar].
+
+use super::blake2s_ref::{IV, SIGMA};
+
+/// Performs a Blake2s compression.
+#[inline]
+pub fn compress(
+    h_vecs: [u32; 8],
+    msg_vecs: [u32; 16],
+    count_low: u32,
+    count_high: u32,
+    lastblock: u32,
+    lastnode: u32,
+) -> [u32; 8] {
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    use neon::compress;
+    #[cfg(not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "ssse3"
+        ),
+        all(target_arch = "aarch64", target_feature = "neon")
+    )))]
+    use scalar::compress;
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "ssse3"
+    ))]
+    use sse2::compress;
+    compress(h_vecs, msg_vecs, count_low, count_high, lastblock, lastnode)
+}
+
+/// The message words of round `R`, in the order of the four vectors consumed by the column and
+/// diagonal mixes of the SIMD implementations.
+///
+/// Storing the words and loading them back as vectors is faster than gathering them with shuffles.
+/// The words go through [black_box](core::hint::black_box) to keep the compiler from turning the
+/// loads back into shuffles.
+#[cfg(any(
+    all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2"
+    ),
+    all(target_arch = "aarch64", target_feature = "neon")
+))]
+#[inline(always)]
+fn message_schedule<const R: usize>(m: &[u32; 16]) -> [u32; 16] {
+    const ORDER: [usize; 16] = [0, 2, 4, 6, 1, 3, 5, 7, 8, 10, 12, 14, 9, 11, 13, 15];
+    core::hint::black_box(core::array::from_fn(|i| m[SIGMA[R][ORDER[i]] as usize]))
+}
+
+/// Portable implementation, with the message schedule resolved at compile time.
+pub mod scalar {
+    use super::{IV, SIGMA};
+
+    /// Mixes the four columns (if `diagonal` is false) or diagonals of the state with the message
+    /// words `x[i]` and `y[i]`. Each step is done on the four of them before the next one, which
+    /// leaves four independent dependency chains for the CPU to interleave.
+    #[inline(always)]
+    fn g4(v: &mut [u32; 16], diagonal: bool, x: [u32; 4], y: [u32; 4]) {
+        let shift = diagonal as usize;
+        let a = [0, 1, 2, 3];
+        let b = a.map(|i| 4 + (i + shift) % 4);
+        let c = a.map(|i| 8 + (i + 2 * shift) % 4);
+        let d = a.map(|i| 12 + (i + 3 * shift) % 4);
+        for i in 0..4 {
+            v[a[i]] = v[a[i]].wrapping_add(v[b[i]]).wrapping_add(x[i]);
+        }
+        for i in 0..4 {
+            v[d[i]] = (v[d[i]] ^ v[a[i]]).rotate_right(16);
+        }
+        for i in 0..4 {
+            v[c[i]] = v[c[i]].wrapping_add(v[d[i]]);
+        }
+        for i in 0..4 {
+            v[b[i]] = (v[b[i]] ^ v[c[i]]).rotate_right(12);
+        }
+        for i in 0..4 {
+            v[a[i]] = v[a[i]].wrapping_add(v[b[i]]).wrapping_add(y[i]);
+        }
+        for i in 0..4 {
+            v[d[i]] = (v[d[i]] ^ v[a[i]]).rotate_right(8);
+        }
+        for i in 0..4 {
+            v[c[i]] = v[c[i]].wrapping_add(v[d[i]]);
+        }
+        for i in 0..4 {
+            v[b[i]] = (v[b[i]] ^ v[c[i]]).rotate_right(7);
+        }
+    }
+
+    #[inline(always)]
+    fn round<const R: usize>(v: &mut [u32; 16], m: &[u32; 16]) {
+        let s = SIGMA[R].map(|i| m[i as usize]);
+        g4(v, false, [s[0], s[2], s[4], s[6]], [s[1], s[3], s[5], s[7]]);
+        g4(
+            v,
+            true,
+            [s[8], s[10], s[12], s[14]],
+            [s[9], s[11], s[13], s[15]],
+        );
+    }
+
+    /// Performs a Blake2s compression.
+    pub fn compress(
+        h_vecs: [u32; 8],
+        msg_vecs: [u32; 16],
+        count_low: u32,
+        count_high: u32,
+        lastblock: u32,
+        lastnode: u32,
+    ) -> [u32; 8] {
+        let mut v = [
+            h_vecs[0],
+            h_vecs[1],
+            h_vecs[2],
+            h_vecs[3],
+            h_vecs[4],
+            h_vecs[5],
+            h_vecs[6],
+            h_vecs[7],
+            IV[0],
+            IV[1],
+            IV[2],
+            IV[3],
+            IV[4] ^ count_low,
+            IV[5] ^ count_high,
+            IV[6] ^ lastblock,
+            IV[7] ^ lastnode,
+        ];
+
+        round::<0>(&mut v, &msg_vecs);
+        round::<1>(&mut v, &msg_vecs);
+        round::<2>(&mut v, &msg_vecs);
+        round::<3>(&mut v, &msg_vecs);
+        round::<4>(&mut v, &msg_vecs);
+        round::<5>(&mut v, &msg_vecs);
+        round::<6>(&mut v, &msg_vecs);
+        round::<7>(&mut v, &msg_vecs);
+        round::<8>(&mut v, &msg_vecs);
+        round::<9>(&mut v, &msg_vecs);
+
+        core::array::from_fn(|i| h_vecs[i] ^ v[i] ^ v[i + 8])
+    }
+}
+
+/// SSE2 implementation, with SSSE3 rotations when available.
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+))]
+pub mod sse2 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    use super::{message_schedule, IV};
+
+    /// Loads the first four words of `words`.
+    #[inline(always)]
+    unsafe fn load(words: &[u32]) -> __m128i {
+        assert!(words.len() >= 4);
+        _mm_loadu_si128(words.as_ptr() as *const __m128i)
+    }
+
+    #[inline(always)]
+    unsafe fn store(row: __m128i) -> [u32; 4] {
+        let mut words = [0; 4];
+        _mm_storeu_si128(words.as_mut_ptr() as *mut __m128i, row);
+        words
+    }
+
+    #[inline(always)]
+    unsafe fn add(a: __m128i, b: __m128i) -> __m128i {
+        _mm_add_epi32(a, b)
+    }
+
+    #[inline(always)]
+    unsafe fn xor(a: __m128i, b: __m128i) -> __m128i {
+        _mm_xor_si128(a, b)
+    }
+
+    #[cfg(target_feature = "ssse3")]
+    #[inline(always)]
+    unsafe fn rot16(x: __m128i) -> __m128i {
+        _mm_shuffle_epi8(
+            x,
+            _mm_set_epi8(13, 12, 15, 14, 9, 8, 11, 10, 5, 4, 7, 6, 1, 0, 3, 2),
+        )
+    }
+
+    #[cfg(not(target_feature = "ssse3"))]
+    #[inline(always)]
+    unsafe fn rot16(x: __m128i) -> __m128i {
+        _mm_shufflehi_epi16::<0b10_11_00_01>(_mm_shufflelo_epi16::<0b10_11_00_01>(x))
+    }
+
+    #[inline(always)]
+    unsafe fn rot12(x: __m128i) -> __m128i {
+        _mm_or_si128(_mm_srli_epi32::<12>(x), _mm_slli_epi32::<20>(x))
+    }
+
+    #[cfg(target_feature = "ssse3")]
+    #[inline(always)]
+    unsafe fn rot8(x: __m128i) -> __m128i {
+        _mm_shuffle_epi8(
+            x,
+            _mm_set_epi8(12, 15, 14, 13, 8, 11, 10, 9, 4, 7, 6, 5, 0, 3, 2, 1),
+        )
+    }
+
+    #[cfg(not(target_feature = "ssse3"))]
+    #[inline(always)]
+    unsafe fn rot8(x: __m128i) -> __m128i {
+        _mm_or_si128(_mm_srli_epi32::<8>(x), _mm_slli_epi32::<24>(x))
+    }
+
+    #[inline(always)]
+    unsafe fn rot7(x: __m128i) -> __m128i {
+        _mm_or_si128(_mm_srli_epi32::<7>(x), _mm_slli_epi32::<25>(x))
+    }
+
+    /// Mixes each column `(a[i], b[i], c[i], d[i])` of the rows with the message words `x[i]` and
+    /// `y[i]`.
+    #[inline(always)]
+    unsafe fn g([a, b, c, d]: &mut [__m128i; 4], x: __m128i, y: __m128i) {
+        *a = add(add(*a, *b), x);
+        *d = rot16(xor(*d, *a));
+        *c = add(*c, *d);
+        *b = rot12(xor(*b, *c));
+        *a = add(add(*a, *b), y);
+        *d = rot8(xor(*d, *a));
+        *c = add(*c, *d);
+        *b = rot7(xor(*b, *c));
+    }
+
+    /// Rotates the rows so that the diagonals of the state become its columns.
+    #[inline(always)]
+    unsafe fn diagonalize([_, b, c, d]: &mut [__m128i; 4]) {
+        *b = _mm_shuffle_epi32::<0b00_11_10_01>(*b);
+        *c = _mm_shuffle_epi32::<0b01_00_11_10>(*c);
+        *d = _mm_shuffle_epi32::<0b10_01_00_11>(*d);
+    }
+
+    /// Inverse of [diagonalize].
+    #[inline(always)]
+    unsafe fn undiagonalize([_, b, c, d]: &mut [__m128i; 4]) {
+        *b = _mm_shuffle_epi32::<0b10_01_00_11>(*b);
+        *c = _mm_shuffle_epi32::<0b01_00_11_10>(*c);
+        *d = _mm_shuffle_epi32::<0b00_11_10_01>(*d);
+    }
+
+    #[inline(always)]
+    unsafe fn round<const R: usize>(rows: &mut [__m128i; 4], m: &[u32; 16]) {
+        let s = message_schedule::<R>(m);
+        g(rows, load(&s[0..4]), load(&s[4..8]));
+        diagonalize(rows);
+        g(rows, load(&s[8..12]), load(&s[12..16]));
+        undiagonalize(rows);
+    }
+
+    #[inline(always)]
+    unsafe fn compress_rows(
+        h_vecs: [u32; 8],
+        msg_vecs: [u32; 16],
+        count_low: u32,
+        count_high: u32,
+        lastblock: u32,
+        lastnode: u32,
+    ) -> [u32; 8] {
+        let h_low = load(&[h_vecs[0], h_vecs[1], h_vecs[2], h_vecs[3]]);
+        let h_high = load(&[h_vecs[4], h_vecs[5], h_vecs[6], h_vecs[7]]);
+        let mut rows = [
+            h_low,
+            h_high,
+            load(&[IV[0], IV[1], IV[2], IV[3]]),
+            xor(
+                load(&[IV[4], IV[5], IV[6], IV[7]]),
+                load(&[count_low, count_high, lastblock, lastnode]),
+            ),
+        ];
+
+        round::<0>(&mut rows, &msg_vecs);
+        round::<1>(&mut rows, &msg_vecs);
+        round::<2>(&mut rows, &msg_vecs);
+        round::<3>(&mut rows, &msg_vecs);
+        round::<4>(&mut rows, &msg_vecs);
+        round::<5>(&mut rows, &msg_vecs);
+        round::<6>(&mut rows, &msg_vecs);
+        round::<7>(&mut rows, &msg_vecs);
+        round::<8>(&mut rows, &msg_vecs);
+        round::<9>(&mut rows, &msg_vecs);
+
+        let [a, b, c, d] = rows;
+        let [l0, l1, l2, l3] = store(xor(h_low, xor(a, c)));
+        let [h0, h1, h2, h3] = store(xor(h_high, xor(b, d)));
+        [l0, l1, l2, l3, h0, h1, h2, h3]
+    }
+
+    /// Performs a Blake2s compression.
+    pub fn compress(
+        h_vecs: [u32; 8],
+        msg_vecs: [u32; 16],
+        count_low: u32,
+        count_high: u32,
+        lastblock: u32,
+        lastnode: u32,
+    ) -> [u32; 8] {
+        // Safety: the module is only compiled when SSE2 is enabled, and the SSSE3 intrinsics are
+        // only used when SSSE3 is enabled.
+        unsafe { compress_rows(h_vecs, msg_vecs, count_low, count_high, lastblock, lastnode) }
+    }
+}
+
+/// NEON implementation.
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub mod neon {
+    use core::arch::aarch64::*;
+
+    use super::{message_schedule, IV};
+
+    /// Loads the first four words of `words`.
+    #[inline(always)]
+    unsafe fn load(words: &[u32]) -> uint32x4_t {
+        assert!(words.len() >= 4);
+        vld1q_u32(words.as_ptr())
+    }
+
+    #[inline(always)]
+    unsafe fn store(row: uint32x4_t) -> [u32; 4] {
+        let mut words = [0; 4];
+        vst1q_u32(words.as_mut_ptr(), row);
+        words
+    }
+
+    #[inline(always)]
+    unsafe fn rot16(x: uint32x4_t) -> uint32x4_t {
+        vreinterpretq_u32_u16(vrev32q_u16(vreinterpretq_u16_u32(x)))
+    }
+
+    #[inline(always)]
+    unsafe fn rot12(x: uint32x4_t) -> uint32x4_t {
+        vsliq_n_u32::<20>(vshrq_n_u32::<12>(x), x)
+    }
+
+    #[inline(always)]
+    unsafe fn rot8(x: uint32x4_t) -> uint32x4_t {
+        vsliq_n_u32::<24>(vshrq_n_u32::<8>(x), x)
+    }
+
+    #[inline(always)]
+    unsafe fn rot7(x: uint32x4_t) -> uint32x4_t {
+        vsliq_n_u32::<25>(vshrq_n_u32::<7>(x), x)
+    }
+
+    /// Mixes each column `(a[i], b[i], c[i], d[i])` of the rows with the message words `x[i]` and
+    /// `y[i]`.
+    #[inline(always)]
+    unsafe fn g([a, b, c, d]: &mut [uint32x4_t; 4], x: uint32x4_t, y: uint32x4_t) {
+        *a = vaddq_u32(vaddq_u32(*a, *b), x);
+        *d = rot16(veorq_u32(*d, *a));
+        *c = vaddq_u32(*c, *d);
+        *b = rot12(veorq_u32(*b, *c));
+        *a = vaddq_u32(vaddq_u32(*a, *b), y);
+        *d = rot8(veorq_u32(*d, *a));
+        *c = vaddq_u32(*c, *d);
+        *b = rot7(veorq_u32(*b, *c));
+    }
+
+    /// Rotates the rows so that the diagonals of the state become its columns.
+    #[inline(always)]
+    unsafe fn diagonalize([_, b, c, d]: &mut [uint32x4_t; 4]) {
+        *b = vextq_u32::<1>(*b, *b);
+        *c = vextq_u32::<2>(*c, *c);
+        *d = vextq_u32::<3>(*d, *d);
+    }
+
+    /// Inverse of [diagonalize].
+    #[inline(always)]
+    unsafe fn undiagonalize([_, b, c, d]: &mut [uint32x4_t; 4]) {
+        *b = vextq_u32::<3>(*b, *b);
+        *c = vextq_u32::<2>(*c, *c);
+        *d = vextq_u32::<1>(*d, *d);
+    }
+
+    #[inline(always)]
+    unsafe fn round<const R: usize>(rows: &mut [uint32x4_t; 4], m: &[u32; 16]) {
+        let s = message_schedule::<R>(m);
+        g(rows, load(&s[0..4]), load(&s[4..8]));
+        diagonalize(rows);
+        g(rows, load(&s[8..12]), load(&s[12..16]));
+        undiagonalize(rows);
+    }
+
+    #[inline(always)]
+    unsafe fn compress_rows(
+        h_vecs: [u32; 8],
+        msg_vecs: [u32; 16],
+        count_low: u32,
+        count_high: u32,
+        lastblock: u32,
+        lastnode: u32,
+    ) -> [u32; 8] {
+        let h_low = load(&[h_vecs[0], h_vecs[1], h_vecs[2], h_vecs[3]]);
+        let h_high = load(&[h_vecs[4], h_vecs[5], h_vecs[6], h_vecs[7]]);
+        let mut rows = [
+            h_low,
+            h_high,
+            load(&[IV[0], IV[1], IV[2], IV[3]]),
+            veorq_u32(
+                load(&[IV[4], IV[5], IV[6], IV[7]]),
+                load(&[count_low, count_high, lastblock, lastnode]),
+            ),
+        ];
+
+        round::<0>(&mut rows, &msg_vecs);
+        round::<1>(&mut rows, &msg_vecs);
+        round::<2>(&mut rows, &msg_vecs);
+        round::<3>(&mut rows, &msg_vecs);
+        round::<4>(&mut rows, &msg_vecs);
+        round::<5>(&mut rows, &msg_vecs);
+        round::<6>(&mut rows, &msg_vecs);
+        round::<7>(&mut rows, &msg_vecs);
+        round::<8>(&mut rows, &msg_vecs);
+        round::<9>(&mut rows, &msg_vecs);
+
+        let [a, b, c, d] = rows;
+        let [l0, l1, l2, l3] = store(veorq_u32(h_low, veorq_u32(a, c)));
+        let [h0, h1, h2, h3] = store(veorq_u32(h_high, veorq_u32(b, d)));
+        [l0, l1, l2, l3, h0, h1, h2, h3]
+    }
+
+    /// Performs a Blake2s compression.
+    pub fn compress(
+        h_vecs: [u32; 8],
+        msg_vecs: [u32; 16],
+        count_low: u32,
+        count_high: u32,
+        lastblock: u32,
+        lastnode: u32,
+    ) -> [u32; 8] {
+        // Safety: the module is only compiled when NEON is enabled.
+        unsafe { compress_rows(h_vecs, msg_vecs, count_low, count_high, lastblock, lastnode) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::super::blake2s_ref;
+
+    type Compress = fn([u32; 8], [u32; 16], u32, u32, u32, u32) -> [u32; 8];
+
+    /// Compares `compress` with the reference implementation on random inputs, including the
+    /// counter and finalization flags.
+    fn assert_matches_reference(compress: Compress) {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let h_vecs = rng.gen();
+            let msg_vecs = rng.gen();
+            let count_low = rng.gen();
+            let count_high = rng.gen();
+            let lastblock = if rng.gen() { u32::MAX } else { 0 };
+            let lastnode = rng.gen();
+
+            assert_eq!(
+                compress(h_vecs, msg_vecs, count_low, count_high, lastblock, lastnode),
+                blake2s_ref::compress(h_vecs, msg_vecs, count_low, count_high, lastblock, lastnode)
+            );
+        }
+    }
+
+    #[test]
+    fn test_compress_matches_reference() {
+        assert_matches_reference(super::compress);
+    }
+
+    #[test]
+    fn test_scalar_compress_matches_reference() {
+        assert_matches_reference(super::scalar::compress);
+    }
+
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2"
+    ))]
+    #[test]
+    fn test_sse2_compress_matches_reference() {
+        assert_matches_reference(super::sse2::compress);
+    }
+
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    #[test]
+    fn test_neon_compress_matches_reference() {
+        assert_matches_reference(super::neon::compress);
+    }
+}
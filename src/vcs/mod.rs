@@ -2,6 +2,7 @@
 
 pub mod blake2_hash;
 pub mod blake2_merkle;
+pub mod blake2s;
 pub mod blake2s_ref;
 // pub mod blake3_hash;
 pub mod hash;
@@ -9,8 +10,9 @@ pub mod ops;
 // #[cfg(not(target_arch = "wasm32"))]
 // pub mod poseidon252_merkle;
 pub mod prover;
+pub mod row_proof;
 mod utils;
 pub mod verifier;
 
-// #[cfg(test)]
-// mod test_utils;
+#[cfg(test)]
+mod test_utils;
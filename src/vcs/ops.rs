@@ -14,6 +14,10 @@ use crate::vcs::hash::Hash;
 /// layer. Each node in that layer contains one value from each column.
 pub trait MerkleHasher: Debug + Default + Clone {
     type Hash: Hash;
+    /// A unique name of the hash function, used to identify it in a
+    /// [`SecurityPolicy`](crate::pcs::SecurityPolicy). Defaults to an empty name, which no policy
+    /// allows.
+    const NAME: &'static str = "";
     /// Hashes a single Merkle node. See [MerkleHasher] for more details.
     fn hash_node(
         children_hashes: Option<(Self::Hash, Self::Hash)>,
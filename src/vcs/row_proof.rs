@@ -0,0 +1,386 @@
+//! Inclusion proofs for a single row of a Merkle commitment.
+//!
+//! A [MerkleRowProof] opens the values of all the columns of one log size at one position. It can
+//! be extracted from a batched [MerkleDecommitment] with [MerkleRowProof::from_decommitment], and
+//! checked against a tree root with [MerkleVerifier::verify_row].
+
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+use thiserror_no_std::Error;
+
+use super::ops::MerkleHasher;
+use super::prover::MerkleDecommitment;
+use super::verifier::{MerkleVerificationError, MerkleVerifier};
+use crate::fields::m31::BaseField;
+
+/// Proof that a row of values is committed in a Merkle tree.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound = "")]
+pub struct MerkleRowProof<H: MerkleHasher> {
+    /// Log size of the columns the row belongs to.
+    pub log_size: u32,
+    /// Index of the row in the columns, which is also the index of its node in the layer.
+    pub position: usize,
+    /// Values of the row, one for each column of size `2^log_size`, in the order they were
+    /// committed.
+    pub values: Vec<BaseField>,
+    /// Hashes of the children of the row's node. [None] if the row is in the largest layer.
+    pub children_hashes: Option<(H::Hash, H::Hash)>,
+    /// The path from the row's node to the root, starting at the layer of the row.
+    pub path: Vec<MerklePathNode<H>>,
+}
+
+/// A step of a [MerkleRowProof] path, from a node to its parent.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound = "")]
+pub struct MerklePathNode<H: MerkleHasher> {
+    /// Hash of the sibling of the node.
+    pub sibling_hash: H::Hash,
+    /// Values of the parent node in the columns of the parent layer's size.
+    pub parent_values: Vec<BaseField>,
+}
+
+impl<H: MerkleHasher> MerkleRowProof<H> {
+    /// Verifies a batched decommitment, as [MerkleVerifier::verify] does, and extracts the proof
+    /// of the row at `position` in the columns of size `2^log_size`.
+    ///
+    /// Returns `Ok(None)` if the row is not part of the decommitment. This is the case when
+    /// neither the row nor any row below it in the tree was queried.
+    pub fn from_decommitment(
+        verifier: &MerkleVerifier<H>,
+        queries_per_log_size: &BTreeMap<u32, Vec<usize>>,
+        queried_values: Vec<BaseField>,
+        decommitment: MerkleDecommitment<H>,
+        log_size: u32,
+        position: usize,
+    ) -> Result<Option<Self>, MerkleVerificationError> {
+        let mut nodes = BTreeMap::new();
+        verifier.verify_with_visitor(
+            queries_per_log_size,
+            queried_values,
+            decommitment,
+            |layer_log_size, node_index, children_hashes, values| {
+                nodes.insert(
+                    (layer_log_size, node_index),
+                    (children_hashes, values.to_vec()),
+                );
+            },
+        )?;
+
+        let Some((children_hashes, values)) = nodes.get(&(log_size, position)).cloned() else {
+            return Ok(None);
+        };
+
+        // Every ancestor of a computed node is computed, with both of its children hashes.
+        let mut path = Vec::with_capacity(log_size as usize);
+        let mut node_index = position;
+        for layer_log_size in (0..log_size).rev() {
+            let (parent_children_hashes, parent_values) = &nodes[&(layer_log_size, node_index / 2)];
+            let (left_hash, right_hash) = parent_children_hashes.unwrap();
+            path.push(MerklePathNode {
+                sibling_hash: if node_index & 1 == 0 {
+                    right_hash
+                } else {
+                    left_hash
+                },
+                parent_values: parent_values.clone(),
+            });
+            node_index /= 2;
+        }
+
+        Ok(Some(Self {
+            log_size,
+            position,
+            values,
+            children_hashes,
+            path,
+        }))
+    }
+
+    /// Encodes the proof in a compact binary format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("serialization into a vector cannot fail")
+    }
+
+    /// Decodes a proof encoded with [MerkleRowProof::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InvalidRowProofEncoding> {
+        postcard::from_bytes(bytes).map_err(|_| InvalidRowProofEncoding)
+    }
+}
+
+/// Error when decoding a [MerkleRowProof] from bytes.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("invalid Merkle row proof encoding")]
+pub struct InvalidRowProofEncoding;
+
+impl<H: MerkleHasher> MerkleVerifier<H> {
+    /// Verifies that the row in `proof` is committed in the tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the following conditions are met:
+    ///
+    /// * The row is outside of the tree.
+    /// * The row doesn't have one value per column of its log size.
+    /// * The children hashes or the path don't match the shape of the tree.
+    /// * The computed root does not match the expected root.
+    pub fn verify_row(&self, proof: &MerkleRowProof<H>) -> Result<(), MerkleVerificationError> {
+        let max_log_size = self.column_log_sizes.iter().max().copied().unwrap_or(0);
+        let n_columns = |log_size| *self.n_columns_per_log_size.get(&log_size).unwrap_or(&0);
+        let MerkleRowProof {
+            log_size,
+            position,
+            ref values,
+            children_hashes,
+            ref path,
+        } = *proof;
+
+        if log_size > max_log_size || position >> log_size != 0 {
+            return Err(MerkleVerificationError::RowOutOfBounds { log_size, position });
+        }
+        if values.len() < n_columns(log_size) {
+            return Err(MerkleVerificationError::TooFewQueriedValues {
+                layer_log_size: log_size,
+                query_position: position,
+            });
+        }
+        if values.len() > n_columns(log_size) {
+            return Err(MerkleVerificationError::TooManyQueriedValues);
+        }
+        match (children_hashes, log_size < max_log_size) {
+            (None, true) => {
+                return Err(MerkleVerificationError::WitnessTooShort {
+                    layer_log_size: log_size + 1,
+                    node_index: 2 * position,
+                })
+            }
+            (Some(_), false) => return Err(MerkleVerificationError::WitnessTooLong),
+            _ => {}
+        }
+        if path.len() > log_size as usize {
+            return Err(MerkleVerificationError::WitnessTooLong);
+        }
+
+        let mut hash = H::hash_node(children_hashes, values);
+        let mut node_index = position;
+        for layer_log_size in (0..log_size).rev() {
+            let Some(path_node) = path.get((log_size - 1 - layer_log_size) as usize) else {
+                return Err(MerkleVerificationError::WitnessTooShort {
+                    layer_log_size: layer_log_size + 1,
+                    node_index: node_index ^ 1,
+                });
+            };
+            let n_parent_values = n_columns(layer_log_size);
+            if path_node.parent_values.len() < n_parent_values {
+                return Err(MerkleVerificationError::WitnessTooShort {
+                    layer_log_size,
+                    node_index: node_index / 2,
+                });
+            }
+            if path_node.parent_values.len() > n_parent_values {
+                return Err(MerkleVerificationError::TooManyWitnessValues {
+                    layer_log_size,
+                    node_index: node_index / 2,
+                });
+            }
+            let children_hashes = if node_index & 1 == 0 {
+                (hash, path_node.sibling_hash)
+            } else {
+                (path_node.sibling_hash, hash)
+            };
+            hash = H::hash_node(Some(children_hashes), &path_node.parent_values);
+            node_index /= 2;
+        }
+
+        if hash != self.root {
+            return Err(MerkleVerificationError::RootMismatch {
+                expected: self.root.to_string(),
+                actual: hash.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use itertools::Itertools;
+
+    use super::MerkleRowProof;
+    use crate::backend::CpuBackend;
+    use crate::fields::m31::BaseField;
+    use crate::vcs::blake2_merkle::Blake2sMerkleHasher;
+    use crate::vcs::prover::MerkleProver;
+    use crate::vcs::verifier::{MerkleVerificationError, MerkleVerifier};
+
+    const LOG_SIZES: [u32; 4] = [4, 2, 4, 3];
+
+    fn columns() -> Vec<Vec<BaseField>> {
+        LOG_SIZES
+            .iter()
+            .enumerate()
+            .map(|(i, &log_size)| {
+                (0..1 << log_size)
+                    .map(|j| BaseField::from(1000 * i + j))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn row_proof(
+        log_size: u32,
+        position: usize,
+    ) -> (
+        MerkleVerifier<Blake2sMerkleHasher>,
+        MerkleRowProof<Blake2sMerkleHasher>,
+    ) {
+        let columns = columns();
+        let queries = BTreeMap::from_iter([(4, vec![3, 9]), (2, vec![1])]);
+        let prover =
+            MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(columns.iter().collect_vec());
+        let (queried_values, decommitment) =
+            prover.decommit(&queries, columns.iter().collect_vec());
+        let verifier = MerkleVerifier::new(prover.root(), LOG_SIZES.to_vec());
+        let proof = MerkleRowProof::from_decommitment(
+            &verifier,
+            &queries,
+            queried_values,
+            decommitment,
+            log_size,
+            position,
+        )
+        .unwrap()
+        .unwrap();
+        (verifier, proof)
+    }
+
+    #[test]
+    fn test_row_proof_from_decommitment() {
+        let columns = columns();
+
+        for (log_size, position) in [(4, 9), (3, 1), (2, 1), (0, 0)] {
+            let (verifier, proof) = row_proof(log_size, position);
+
+            verifier.verify_row(&proof).unwrap();
+            assert_eq!(proof.path.len(), log_size as usize);
+            let expected_values = LOG_SIZES
+                .iter()
+                .zip(&columns)
+                .filter(|(&column_log_size, _)| column_log_size == log_size)
+                .map(|(_, column)| column[position])
+                .collect_vec();
+            assert_eq!(proof.values, expected_values);
+        }
+    }
+
+    #[test]
+    fn test_row_proof_not_decommitted() {
+        let columns = columns();
+        let queries = BTreeMap::from_iter([(4, vec![3])]);
+        let prover =
+            MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(columns.iter().collect_vec());
+        let (queried_values, decommitment) =
+            prover.decommit(&queries, columns.iter().collect_vec());
+        let verifier = MerkleVerifier::new(prover.root(), LOG_SIZES.to_vec());
+
+        let proof = MerkleRowProof::from_decommitment(
+            &verifier,
+            &queries,
+            queried_values,
+            decommitment,
+            4,
+            4,
+        );
+
+        assert_eq!(proof, Ok(None));
+    }
+
+    #[test]
+    fn test_row_proof_invalid_value() {
+        let (verifier, mut proof) = row_proof(4, 9);
+        proof.values[1] += BaseField::from(1);
+
+        assert!(matches!(
+            verifier.verify_row(&proof),
+            Err(MerkleVerificationError::RootMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_row_proof_invalid_path() {
+        let (verifier, mut proof) = row_proof(3, 1);
+        proof.path.pop();
+
+        assert_eq!(
+            verifier.verify_row(&proof),
+            Err(MerkleVerificationError::WitnessTooShort {
+                layer_log_size: 1,
+                node_index: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_row_proof_invalid_parent_values() {
+        let (verifier, proof) = row_proof(4, 9);
+        let mut short_proof = proof.clone();
+        short_proof.path[0].parent_values.pop();
+        let mut long_proof = proof;
+        long_proof.path[0].parent_values.push(BaseField::from(1));
+
+        assert_eq!(
+            verifier.verify_row(&short_proof),
+            Err(MerkleVerificationError::WitnessTooShort {
+                layer_log_size: 3,
+                node_index: 4
+            })
+        );
+        assert_eq!(
+            verifier.verify_row(&long_proof),
+            Err(MerkleVerificationError::TooManyWitnessValues {
+                layer_log_size: 3,
+                node_index: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_row_proof_out_of_bounds() {
+        let (verifier, mut proof) = row_proof(2, 1);
+        proof.position = 4;
+
+        assert_eq!(
+            verifier.verify_row(&proof),
+            Err(MerkleVerificationError::RowOutOfBounds {
+                log_size: 2,
+                position: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_row_proof_encoding() {
+        let (verifier, proof) = row_proof(4, 3);
+
+        let bytes = proof.to_bytes();
+        let decoded = MerkleRowProof::from_bytes(&bytes).unwrap();
+        let json = serde_json::to_string(&proof).unwrap();
+
+        assert_eq!(decoded, proof);
+        assert_eq!(
+            serde_json::from_str::<MerkleRowProof<_>>(&json).unwrap(),
+            proof
+        );
+        verifier.verify_row(&decoded).unwrap();
+        assert!(
+            MerkleRowProof::<Blake2sMerkleHasher>::from_bytes(&bytes[..bytes.len() - 1]).is_err()
+        );
+    }
+}
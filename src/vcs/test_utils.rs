@@ -0,0 +1,67 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use super::ops::{MerkleHasher, MerkleOps};
+use super::prover::{MerkleDecommitment, MerkleProver};
+use super::verifier::MerkleVerifier;
+use crate::backend::CpuBackend;
+use crate::fields::m31::BaseField;
+
+pub type TestData<H> = (
+    BTreeMap<u32, Vec<usize>>,
+    MerkleDecommitment<H>,
+    Vec<BaseField>,
+    MerkleVerifier<H>,
+);
+
+pub fn prepare_merkle<H: MerkleHasher>() -> TestData<H>
+where
+    CpuBackend: MerkleOps<H>,
+{
+    prepare_random_merkle(0, 10, 3..5, 3)
+}
+
+/// Commits to `n_cols` random columns with log sizes in `log_size_range`, and decommits up to
+/// `n_queries` random positions for each log size in the range.
+pub fn prepare_random_merkle<H: MerkleHasher>(
+    seed: u64,
+    n_cols: usize,
+    log_size_range: core::ops::Range<u32>,
+    n_queries: usize,
+) -> TestData<H>
+where
+    CpuBackend: MerkleOps<H>,
+{
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let log_sizes = (0..n_cols)
+        .map(|_| rng.gen_range(log_size_range.clone()))
+        .collect_vec();
+    let cols = log_sizes
+        .iter()
+        .map(|&log_size| {
+            (0..(1 << log_size))
+                .map(|_| BaseField::from(rng.gen_range(0..(1 << 30))))
+                .collect_vec()
+        })
+        .collect_vec();
+    let merkle = MerkleProver::<CpuBackend, H>::commit(cols.iter().collect_vec());
+
+    let mut queries = BTreeMap::<u32, Vec<usize>>::new();
+    for log_size in log_size_range.rev() {
+        let layer_queries = (0..n_queries)
+            .map(|_| rng.gen_range(0..(1 << log_size)))
+            .sorted()
+            .dedup()
+            .collect_vec();
+        queries.insert(log_size, layer_queries);
+    }
+
+    let (values, decommitment) = merkle.decommit(&queries, cols.iter().collect_vec());
+
+    let verifier = MerkleVerifier::new(merkle.root(), log_sizes);
+    (queries, decommitment, values, verifier)
+}
@@ -1,6 +1,7 @@
 // use alloc::collections::BTreeMap;
 use alloc::collections::BTreeMap;
 
+use alloc::string::{String, ToString};
 use alloc::vec;
 use alloc::vec::Vec;
 use itertools::Itertools;
@@ -52,12 +53,29 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
     /// * Too few queried values (missing values).
     /// * The computed root does not match the expected root.
     ///
-    /// [`MerkleProver::decommit()`]: crate::core::...::MerkleProver::decommit
+    /// [`MerkleProver::decommit()`]: crate::vcs::prover::MerkleProver::decommit
     pub fn verify(
         &self,
         queries_per_log_size: &BTreeMap<u32, Vec<usize>>,
         queried_values: Vec<BaseField>,
         decommitment: MerkleDecommitment<H>,
+    ) -> Result<(), MerkleVerificationError> {
+        self.verify_with_visitor(
+            queries_per_log_size,
+            queried_values,
+            decommitment,
+            |_, _, _, _| {},
+        )
+    }
+
+    /// Same as [MerkleVerifier::verify], but calls `visit_node` with the layer log size, index,
+    /// children hashes and column values of every node it computes on the way to the root.
+    pub(super) fn verify_with_visitor(
+        &self,
+        queries_per_log_size: &BTreeMap<u32, Vec<usize>>,
+        queried_values: Vec<BaseField>,
+        decommitment: MerkleDecommitment<H>,
+        mut visit_node: impl FnMut(u32, usize, Option<(H::Hash, H::Hash)>, &[BaseField]),
     ) -> Result<(), MerkleVerificationError> {
         let Some(max_log_size) = self.column_log_sizes.iter().max() else {
             return Ok(());
@@ -110,9 +128,12 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
                                 .next_if(|(index, _)| *index == 2 * node_index)
                                 .map(|(_, hash)| Ok(*hash))
                                 .unwrap_or_else(|| {
-                                    hash_witness
-                                        .next()
-                                        .ok_or(MerkleVerificationError::WitnessTooShort)
+                                    hash_witness.next().ok_or(
+                                        MerkleVerificationError::WitnessTooShort {
+                                            layer_log_size: layer_log_size + 1,
+                                            node_index: 2 * node_index,
+                                        },
+                                    )
                                 })?;
 
                             // If the right child was not computed, read it to from the witness.
@@ -120,9 +141,12 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
                                 .next_if(|(index, _)| *index == 2 * node_index + 1)
                                 .map(|(_, hash)| Ok(*hash))
                                 .unwrap_or_else(|| {
-                                    hash_witness
-                                        .next()
-                                        .ok_or(MerkleVerificationError::WitnessTooShort)
+                                    hash_witness.next().ok_or(
+                                        MerkleVerificationError::WitnessTooShort {
+                                            layer_log_size: layer_log_size + 1,
+                                            node_index: 2 * node_index + 1,
+                                        },
+                                    )
                                 })?;
                             Ok((left_hash, right_hash))
                         }
@@ -132,12 +156,18 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
                 // If the column values were queried, read them from `queried_value`.
                 let (err, node_values_iter) = match layer_column_queries.next_if_eq(&node_index) {
                     Some(_) => (
-                        MerkleVerificationError::TooFewQueriedValues,
+                        MerkleVerificationError::TooFewQueriedValues {
+                            layer_log_size,
+                            query_position: node_index,
+                        },
                         &mut queried_values,
                     ),
                     // Otherwise, read them from the witness.
                     None => (
-                        MerkleVerificationError::WitnessTooShort,
+                        MerkleVerificationError::WitnessTooShort {
+                            layer_log_size,
+                            node_index,
+                        },
                         &mut column_witness,
                     ),
                 };
@@ -146,6 +176,7 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
                 if node_values.len() != n_columns_in_layer {
                     return Err(err);
                 }
+                visit_node(layer_log_size, node_index, node_hashes, &node_values);
                 layer_total_queries.push((node_index, H::hash_node(node_hashes, &node_values)));
             }
             last_layer_hashes = Some(layer_total_queries);
@@ -164,24 +195,230 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
 
         let [(_, computed_root)] = last_layer_hashes.unwrap().try_into().unwrap();
         if computed_root != self.root {
-            return Err(MerkleVerificationError::RootMismatch);
+            return Err(MerkleVerificationError::RootMismatch {
+                expected: self.root.to_string(),
+                actual: computed_root.to_string(),
+            });
         }
 
         Ok(())
     }
+
+    /// Verifies the decommitment of the columns, like [MerkleVerifier::verify], and returns the
+    /// authenticated queried values arranged by log size and query position.
+    pub fn verify_openings(
+        &self,
+        queries_per_log_size: &BTreeMap<u32, Vec<usize>>,
+        queried_values: Vec<BaseField>,
+        decommitment: MerkleDecommitment<H>,
+    ) -> Result<VerifiedOpenings, MerkleVerificationError> {
+        let openings = self.split_queried_values(queries_per_log_size, &queried_values);
+        self.verify(queries_per_log_size, queried_values, decommitment)?;
+        Ok(openings)
+    }
+
+    /// Splits `queried_values` into rows, following the order in [`MerkleProver::decommit()`].
+    ///
+    /// Stops at the first row that is missing values; [MerkleVerifier::verify] rejects such input.
+    ///
+    /// [`MerkleProver::decommit()`]: crate::vcs::prover::MerkleProver::decommit
+    fn split_queried_values(
+        &self,
+        queries_per_log_size: &BTreeMap<u32, Vec<usize>>,
+        queried_values: &[BaseField],
+    ) -> VerifiedOpenings {
+        let mut rows = BTreeMap::new();
+        let mut remaining_values = queried_values;
+        for (&log_size, &n_columns) in self.n_columns_per_log_size.iter().rev() {
+            let log_size_rows: &mut BTreeMap<usize, Vec<BaseField>> =
+                rows.entry(log_size).or_default();
+            for &position in queries_per_log_size.get(&log_size).into_iter().flatten() {
+                if remaining_values.len() < n_columns {
+                    return VerifiedOpenings(rows);
+                }
+                let (row, rest) = remaining_values.split_at(n_columns);
+                log_size_rows.insert(position, row.to_vec());
+                remaining_values = rest;
+            }
+        }
+        VerifiedOpenings(rows)
+    }
+}
+
+/// Column values authenticated by [MerkleVerifier::verify_openings].
+///
+/// Maps each column log size to the queried positions in columns of that size, and each position
+/// to the row of values of those columns, in the order they were committed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifiedOpenings(pub BTreeMap<u32, BTreeMap<usize, Vec<BaseField>>>);
+
+impl VerifiedOpenings {
+    /// Returns the values at `position` of the columns of size `2^log_size`, if it was queried.
+    pub fn row(&self, log_size: u32, position: usize) -> Option<&[BaseField]> {
+        self.0.get(&log_size)?.get(&position).map(Vec::as_slice)
+    }
 }
 
-// TODO(ilya): Make error messages consistent.
-#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+/// A failed Merkle decommitment check, with the location in the tree where it was detected.
+///
+/// Hashes are formatted with their [Display](core::fmt::Display) implementation, which is hex for
+/// [Blake2sHash](crate::vcs::blake2_hash::Blake2sHash).
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
 pub enum MerkleVerificationError {
-    #[error("Witness is too short.")]
-    WitnessTooShort,
-    #[error("Witness is too long.")]
+    #[error("witness is too short at node {node_index} of layer {layer_log_size}")]
+    WitnessTooShort {
+        layer_log_size: u32,
+        node_index: usize,
+    },
+    #[error("witness is too long")]
     WitnessTooLong,
-    #[error("too many Queried values")]
+    #[error("too many queried values")]
     TooManyQueriedValues,
-    #[error("too few queried values")]
-    TooFewQueriedValues,
-    #[error("Root mismatch.")]
-    RootMismatch,
+    #[error("too few queried values at query {query_position} of layer {layer_log_size}")]
+    TooFewQueriedValues {
+        layer_log_size: u32,
+        query_position: usize,
+    },
+    #[error("root mismatch: expected {expected}, computed {actual}")]
+    RootMismatch { expected: String, actual: String },
+    #[error("row {position} of log size {log_size} is outside of the tree")]
+    RowOutOfBounds { log_size: u32, position: usize },
+    #[error("witness has too many values at node {node_index} of layer {layer_log_size}")]
+    TooManyWitnessValues {
+        layer_log_size: u32,
+        node_index: usize,
+    },
+}
+
+impl MerkleVerificationError {
+    /// A stable numeric code identifying the variant. Codes are never reused or reassigned.
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::WitnessTooShort { .. } => 100,
+            Self::WitnessTooLong => 101,
+            Self::TooManyQueriedValues => 102,
+            Self::TooFewQueriedValues { .. } => 103,
+            Self::RootMismatch { .. } => 104,
+            Self::RowOutOfBounds { .. } => 105,
+            Self::TooManyWitnessValues { .. } => 106,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use itertools::Itertools;
+
+    use super::{MerkleVerificationError, MerkleVerifier};
+    use crate::backend::CpuBackend;
+    use crate::fields::m31::BaseField;
+    use crate::vcs::blake2_merkle::Blake2sMerkleHasher;
+    use crate::vcs::ops::MerkleHasher;
+    use crate::vcs::prover::{MerkleDecommitment, MerkleProver};
+
+    /// Returns the root of a tree with a single column `[v0, v1]`.
+    fn root(v0: BaseField, v1: BaseField) -> <Blake2sMerkleHasher as MerkleHasher>::Hash {
+        let left = Blake2sMerkleHasher::hash_node(None, &[v0]);
+        let right = Blake2sMerkleHasher::hash_node(None, &[v1]);
+        Blake2sMerkleHasher::hash_node(Some((left, right)), &[])
+    }
+
+    #[test]
+    fn test_merkle_errors_are_located() {
+        let (v0, v1) = (BaseField::from(3), BaseField::from(5));
+        let queries = BTreeMap::from_iter([(1, vec![0])]);
+        let sibling_hash = Blake2sMerkleHasher::hash_node(None, &[v1]);
+        let decommitment = |hash_witness| MerkleDecommitment::<Blake2sMerkleHasher> {
+            hash_witness,
+            column_witness: vec![],
+        };
+        let verifier = MerkleVerifier::<Blake2sMerkleHasher>::new(root(v0, v1), vec![1]);
+        verifier
+            .verify(&queries, vec![v0], decommitment(vec![sibling_hash]))
+            .unwrap();
+
+        let short_witness = verifier
+            .verify(&queries, vec![v0], decommitment(vec![]))
+            .unwrap_err();
+        let wrong_value = verifier
+            .verify(&queries, vec![v1], decommitment(vec![sibling_hash]))
+            .unwrap_err();
+
+        assert_eq!(
+            short_witness,
+            MerkleVerificationError::WitnessTooShort {
+                layer_log_size: 1,
+                node_index: 1
+            }
+        );
+        assert_eq!(short_witness.code(), 100);
+        assert_eq!(
+            wrong_value,
+            MerkleVerificationError::RootMismatch {
+                expected: root(v0, v1).to_string(),
+                actual: root(v1, v1).to_string(),
+            }
+        );
+        assert_eq!(wrong_value.code(), 104);
+    }
+
+    #[test]
+    fn test_verify_openings() {
+        let log_sizes = [3, 2, 3, 1];
+        let columns = log_sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &log_size)| {
+                (0..1 << log_size)
+                    .map(|j| BaseField::from(100 * i + j))
+                    .collect::<Vec<_>>()
+            })
+            .collect_vec();
+        let queries = BTreeMap::from_iter([(3, vec![1, 6]), (2, vec![0]), (1, vec![1])]);
+        let prover =
+            MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(columns.iter().collect_vec());
+        let (queried_values, decommitment) =
+            prover.decommit(&queries, columns.iter().collect_vec());
+        let verifier =
+            MerkleVerifier::<Blake2sMerkleHasher>::new(prover.root(), log_sizes.to_vec());
+
+        let openings = verifier
+            .verify_openings(&queries, queried_values, decommitment)
+            .unwrap();
+
+        assert_eq!(
+            openings.row(3, 1),
+            Some(&[columns[0][1], columns[2][1]][..])
+        );
+        assert_eq!(
+            openings.row(3, 6),
+            Some(&[columns[0][6], columns[2][6]][..])
+        );
+        assert_eq!(openings.row(2, 0), Some(&[columns[1][0]][..]));
+        assert_eq!(openings.row(1, 1), Some(&[columns[3][1]][..]));
+        assert_eq!(openings.row(3, 0), None);
+        assert_eq!(openings.0.values().map(|rows| rows.len()).sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_verify_openings_rejects_wrong_values() {
+        let columns = [(0..8).map(BaseField::from).collect::<Vec<_>>()];
+        let queries = BTreeMap::from_iter([(3, vec![2])]);
+        let prover =
+            MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(columns.iter().collect_vec());
+        let (_, decommitment) = prover.decommit(&queries, columns.iter().collect_vec());
+        let verifier = MerkleVerifier::<Blake2sMerkleHasher>::new(prover.root(), vec![3]);
+
+        let result = verifier.verify_openings(&queries, vec![BaseField::from(5)], decommitment);
+
+        assert!(matches!(
+            result,
+            Err(MerkleVerificationError::RootMismatch { .. })
+        ));
+    }
 }
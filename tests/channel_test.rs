@@ -0,0 +1,82 @@
+use stwo_prover::core::channel::{Blake2sChannel, Channel};
+use stwo_prover::core::fields::qm31::SecureField;
+use stwo_prover::core::vcs::blake2_hash::Blake2sHasher;
+
+use stwo_verifier_no_std::channel::{Blake2sChannel as VerifierChannel, Channel as _};
+use stwo_verifier_no_std::fields::qm31::SecureField as VerifierSecureField;
+
+fn to_verifier_felts(felts: &[SecureField]) -> Vec<VerifierSecureField> {
+    felts
+        .iter()
+        .map(|felt| {
+            let [a, b, c, d] = felt.to_m31_array().map(|x| x.0);
+            VerifierSecureField::from_u32_unchecked(a, b, c, d)
+        })
+        .collect()
+}
+
+#[test]
+fn test_channel_matches_upstream() {
+    let mut channel = Blake2sChannel::default();
+    let mut verifier_channel = VerifierChannel::default();
+    let felts = [
+        SecureField::from_u32_unchecked(1, 2, 3, 4),
+        SecureField::from_u32_unchecked(5, 6, 7, 8),
+    ];
+
+    channel.mix_felts(&felts);
+    verifier_channel.mix_felts(&to_verifier_felts(&felts));
+    channel.mix_u64(0x1234_5678_9abc_def0);
+    verifier_channel.mix_u64(0x1234_5678_9abc_def0);
+
+    assert_eq!(channel.digest().0, verifier_channel.digest().0);
+    assert_eq!(
+        to_verifier_felts(&channel.draw_felts(5)),
+        verifier_channel.draw_felts(5)
+    );
+    assert_eq!(
+        channel.draw_random_bytes(),
+        verifier_channel.draw_random_bytes()
+    );
+}
+
+#[test]
+fn test_mix_u32s_matches_upstream_mix_felts() {
+    let mut channel = Blake2sChannel::default();
+    let mut verifier_channel = VerifierChannel::default();
+
+    channel.mix_felts(&[
+        SecureField::from_u32_unchecked(11, 22, 33, 44),
+        SecureField::from_u32_unchecked(55, 66, 77, 88),
+    ]);
+    verifier_channel.mix_u32s(&[11, 22, 33, 44, 55, 66, 77, 88]);
+
+    assert_eq!(channel.digest().0, verifier_channel.digest().0);
+}
+
+#[test]
+fn test_draw_u32s_matches_upstream_random_bytes() {
+    let mut channel = Blake2sChannel::default();
+    let mut verifier_channel = VerifierChannel::default();
+
+    let random_u32s = channel
+        .draw_random_bytes()
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(random_u32s, verifier_channel.draw_u32s());
+}
+
+#[test]
+fn test_domain_separator_matches_upstream_digest() {
+    let mut channel = Blake2sChannel::default();
+    channel.update_digest(Blake2sHasher::hash(b"stwo-verifier"));
+    let mut verifier_channel = VerifierChannel::with_domain_separator(b"stwo-verifier");
+
+    assert_eq!(channel.digest().0, verifier_channel.digest().0);
+    assert_eq!(
+        to_verifier_felts(&[channel.draw_felt()]),
+        [verifier_channel.draw_felt()]
+    );
+}
@@ -297,6 +297,114 @@ mod tests {
         }
     }
 
+    /// Proves the wide Fibonacci component with the steps of `prove`, but commits the composition
+    /// polynomial in the split format, as the halves of its coefficients.
+    #[test]
+    fn test_wide_fib_prove_with_split_composition() {
+        use stwo_prover::core::air::{ComponentProver, ComponentProvers};
+        use stwo_prover::core::channel::Channel;
+        use stwo_prover::core::circle::CirclePoint;
+        use stwo_prover::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
+        use stwo_prover::core::poly::circle::CirclePoly;
+        use stwo_verifier_no_std::channel::Blake2sChannel as Blake2sChannelVerifier;
+        use stwo_verifier_no_std::constraint_framework::FrameworkComponent;
+        use stwo_verifier_no_std::pcs::CommitmentSchemeVerifier as CommitmentSchemeVerifierVerifier;
+        use stwo_verifier_no_std::pcs::PcsConfig as PcsConfigVerifier;
+        use stwo_verifier_no_std::vcs::blake2_merkle::Blake2sMerkleChannel as Blake2sMerkleChannelVerifier;
+        use stwo_verifier_no_std::vcs::blake2_merkle::Blake2sMerkleHasher as Blake2sMerkleHasherVerifier;
+        use stwo_verifier_no_std::{
+            verify_with_format, CompositionPolyFormat, StarkProof as StarkProofVerifier,
+        };
+
+        for log_n_instances in 2..=6 {
+            let config = PcsConfig::default();
+            let twiddles = SimdBackend::precompute_twiddles(
+                CanonicCoset::new(log_n_instances + 1 + config.fri_config.log_blowup_factor)
+                    .circle_domain()
+                    .half_coset,
+            );
+            let prover_channel = &mut Blake2sChannel::default();
+            let mut commitment_scheme =
+                CommitmentSchemeProver::<SimdBackend, Blake2sMerkleChannel>::new(config, &twiddles);
+
+            // Preprocessed trace.
+            let mut tree_builder = commitment_scheme.tree_builder();
+            tree_builder.extend_evals([]);
+            tree_builder.commit(prover_channel);
+
+            // Trace.
+            let mut tree_builder = commitment_scheme.tree_builder();
+            tree_builder.extend_evals(generate_test_trace(log_n_instances));
+            tree_builder.commit(prover_channel);
+
+            let component = WideFibonacciComponent::new(
+                &mut TraceLocationAllocator::default(),
+                WideFibonacciEval::<FIB_SEQUENCE_LENGTH> {
+                    log_n_rows: log_n_instances,
+                },
+                SecureField::zero(),
+            );
+            let component_provers =
+                ComponentProvers(vec![&component as &dyn ComponentProver<SimdBackend>]);
+
+            // Commit to the halves of the coefficients of each coordinate polynomial.
+            let random_coeff = prover_channel.draw_felt();
+            let composition_poly = component_provers
+                .compute_composition_polynomial(random_coeff, &commitment_scheme.trace());
+            let (left_polys, right_polys): (Vec<_>, Vec<_>) = composition_poly
+                .into_coordinate_polys()
+                .into_iter()
+                .map(|poly| {
+                    let coeffs = poly.coeffs.to_cpu();
+                    let (left, right) = coeffs.split_at(coeffs.len() / 2);
+                    (
+                        CirclePoly::<SimdBackend>::new(left.iter().copied().collect()),
+                        CirclePoly::<SimdBackend>::new(right.iter().copied().collect()),
+                    )
+                })
+                .unzip();
+            let mut tree_builder = commitment_scheme.tree_builder();
+            tree_builder.extend_polys(left_polys.into_iter().chain(right_polys));
+            tree_builder.commit(prover_channel);
+
+            let oods_point = CirclePoint::<SecureField>::get_random_point(prover_channel);
+            let mut sample_points = component_provers.components().mask_points(oods_point);
+            sample_points.push(vec![vec![oods_point]; 2 * SECURE_EXTENSION_DEGREE]);
+            let proof = commitment_scheme.prove_values(sample_points, prover_channel);
+            let ser_proof = serde_json::to_string(&proof).unwrap();
+
+            // Verify in each format.
+            let sizes = component.trace_log_degree_bounds();
+            let verifier_component = FrameworkComponent::new(
+                &mut stwo_verifier_no_std::constraint_framework::TraceLocationAllocator::default(),
+                WideFibonacciEval::<FIB_SEQUENCE_LENGTH> {
+                    log_n_rows: log_n_instances,
+                },
+                stwo_verifier_no_std::fields::qm31::SecureField::zero(),
+            );
+            let verify_in_format = |format| {
+                let proof: StarkProofVerifier<Blake2sMerkleHasherVerifier> =
+                    serde_json::from_str(&ser_proof).unwrap();
+                let verifier_channel = &mut Blake2sChannelVerifier::default();
+                let commitment_scheme = &mut CommitmentSchemeVerifierVerifier::<
+                    Blake2sMerkleChannelVerifier,
+                >::new(PcsConfigVerifier::default());
+                commitment_scheme.commit(proof.commitments[0], &sizes[0], verifier_channel);
+                commitment_scheme.commit(proof.commitments[1], &sizes[1], verifier_channel);
+                verify_with_format(
+                    &[&verifier_component],
+                    verifier_channel,
+                    commitment_scheme,
+                    proof,
+                    format,
+                )
+            };
+
+            verify_in_format(CompositionPolyFormat::Split).unwrap();
+            assert!(verify_in_format(CompositionPolyFormat::Single).is_err());
+        }
+    }
+
     // #[test]
     // #[cfg(not(target_arch = "wasm32"))]
     // fn test_wide_fib_prove_with_poseidon() {
@@ -0,0 +1,139 @@
+use itertools::Itertools;
+use stwo_prover::core::backend::cpu::CpuCirclePoly;
+use stwo_prover::core::backend::CpuBackend;
+use stwo_prover::core::channel::Blake2sChannel;
+use stwo_prover::core::circle::CirclePoint;
+use stwo_prover::core::fields::m31::BaseField;
+use stwo_prover::core::fields::qm31::SecureField;
+use stwo_prover::core::pcs::{CommitmentSchemeProver, PcsConfig, TreeVec};
+use stwo_prover::core::poly::circle::{CanonicCoset, PolyOps};
+use stwo_prover::core::vcs::blake2_merkle::Blake2sMerkleChannel;
+
+use stwo_verifier_no_std::channel::Blake2sChannel as VerifierChannel;
+use stwo_verifier_no_std::circle::CirclePoint as VerifierCirclePoint;
+use stwo_verifier_no_std::fields::qm31::SecureField as VerifierSecureField;
+use stwo_verifier_no_std::pcs::{
+    CommitmentSchemeProof as VerifierCommitmentSchemeProof,
+    CommitmentSchemeVerifier as VerifierCommitmentScheme, PcsConfig as VerifierPcsConfig,
+    TreeVec as VerifierTreeVec,
+};
+use stwo_verifier_no_std::vcs::blake2_merkle::{
+    Blake2sMerkleChannel as VerifierMerkleChannel, Blake2sMerkleHasher as VerifierMerkleHasher,
+};
+use stwo_verifier_no_std::VerificationError;
+
+/// Log sizes of the committed columns, per tree.
+const TREE_LOG_SIZES: [&[u32]; 3] = [&[4, 6], &[5, 5, 7], &[3]];
+
+/// For each column, the indices of the random points at which it is opened.
+const OPENED_POINTS: [&[&[usize]]; 3] = [&[&[0], &[0, 1]], &[&[1], &[0, 1, 2], &[]], &[&[2, 0]]];
+
+const N_POINTS: usize = 3;
+
+fn test_polys(log_sizes: &[u32]) -> Vec<CpuCirclePoly> {
+    log_sizes
+        .iter()
+        .enumerate()
+        .map(|(i, &log_size)| {
+            CpuCirclePoly::new(
+                (0..1 << log_size)
+                    .map(|j| BaseField::from_u32_unchecked((i * 1000 + j * 7 + 3) as u32))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+fn sampled_points<P: Copy>(points: &[P]) -> Vec<Vec<Vec<P>>> {
+    OPENED_POINTS
+        .iter()
+        .map(|tree| {
+            tree.iter()
+                .map(|column| column.iter().map(|&i| points[i]).collect())
+                .collect()
+        })
+        .collect()
+}
+
+/// Commits to the test columns with the stwo prover and opens them at random points.
+fn prove_openings(config: PcsConfig) -> String {
+    let max_log_size = TREE_LOG_SIZES.iter().flat_map(|t| t.iter()).max().unwrap();
+    let twiddles = CpuBackend::precompute_twiddles(
+        CanonicCoset::new(max_log_size + config.fri_config.log_blowup_factor)
+            .circle_domain()
+            .half_coset,
+    );
+    let channel = &mut Blake2sChannel::default();
+    let mut commitment_scheme =
+        CommitmentSchemeProver::<CpuBackend, Blake2sMerkleChannel>::new(config, &twiddles);
+    for log_sizes in TREE_LOG_SIZES {
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_polys(test_polys(log_sizes));
+        tree_builder.commit(channel);
+    }
+
+    let points = (0..N_POINTS)
+        .map(|_| CirclePoint::<SecureField>::get_random_point(channel))
+        .collect_vec();
+    let proof = commitment_scheme.prove_values(TreeVec::new(sampled_points(&points)), channel);
+    serde_json::to_string(&proof).unwrap()
+}
+
+/// Verifies the serialized opening proof through the standalone PCS API.
+fn verify_openings(
+    serialized_proof: &str,
+    tamper: impl FnOnce(&mut VerifierTreeVec<Vec<Vec<VerifierSecureField>>>),
+) -> Result<(), VerificationError> {
+    let proof: VerifierCommitmentSchemeProof<VerifierMerkleHasher> =
+        serde_json::from_str(serialized_proof).unwrap();
+    let channel = &mut VerifierChannel::default();
+    let commitment_scheme =
+        &mut VerifierCommitmentScheme::<VerifierMerkleChannel>::new(VerifierPcsConfig::default());
+    for (&root, log_sizes) in proof.commitments.iter().zip(TREE_LOG_SIZES) {
+        commitment_scheme.commit(root, log_sizes, channel);
+    }
+
+    let points = (0..N_POINTS)
+        .map(|_| VerifierCirclePoint::<VerifierSecureField>::get_random_point(channel))
+        .collect_vec();
+    let (mut sampled_values, opening_proof) = proof.into_opening();
+    tamper(&mut sampled_values);
+    commitment_scheme.verify_opening(
+        VerifierTreeVec::new(sampled_points(&points)),
+        sampled_values,
+        opening_proof,
+        channel,
+    )
+}
+
+#[test]
+fn test_pcs_opening_mixed_sizes_and_points() {
+    let proof = prove_openings(PcsConfig::default());
+
+    verify_openings(&proof, |_| {}).unwrap();
+}
+
+#[test]
+fn test_pcs_opening_wrong_value_fails() {
+    let proof = prove_openings(PcsConfig::default());
+
+    let result = verify_openings(&proof, |sampled_values| {
+        sampled_values[1][1][2] += VerifierSecureField::from_u32_unchecked(1, 0, 0, 0);
+    });
+
+    assert!(matches!(result, Err(VerificationError::Fri(_))));
+}
+
+#[test]
+fn test_pcs_opening_wrong_structure_fails() {
+    let proof = prove_openings(PcsConfig::default());
+
+    let result = verify_openings(&proof, |sampled_values| {
+        sampled_values[2][0].pop();
+    });
+
+    assert!(matches!(
+        result,
+        Err(VerificationError::InvalidStructure(_))
+    ));
+}
@@ -0,0 +1,46 @@
+use stwo_verifier_no_std::channel::{Blake2sChannel, Channel};
+use stwo_verifier_no_std::constraint_framework::logup::LookupElements;
+use stwo_verifier_no_std::constraint_framework::Relation;
+use stwo_verifier_no_std::fields::m31::BaseField;
+use stwo_verifier_no_std::fields::qm31::SecureField;
+use stwo_verifier_no_std::relation;
+
+relation!(TestRelation, 2);
+
+#[test]
+fn test_relation_macro_draws_lookup_elements() {
+    let mut channel = Blake2sChannel::default();
+    channel.mix_u64(42);
+    let mut expected_channel = channel.clone();
+
+    let relation = TestRelation::draw(&mut channel);
+
+    assert_eq!(relation.0, LookupElements::draw(&mut expected_channel));
+    assert_eq!(channel.digest(), expected_channel.digest());
+}
+
+#[test]
+fn test_relation_macro_combines_values() {
+    let mut channel = Blake2sChannel::default();
+    let relation = TestRelation::draw(&mut channel);
+    let values = [BaseField::from(3), BaseField::from(5)];
+
+    let combined: SecureField = relation.combine(&values);
+
+    assert_eq!(combined, relation.0.combine::<_, SecureField>(&values));
+    assert_eq!(
+        <TestRelation as Relation<BaseField, SecureField>>::get_name(&relation),
+        "TestRelation"
+    );
+    assert_eq!(
+        <TestRelation as Relation<BaseField, SecureField>>::get_size(&relation),
+        2
+    );
+}
+
+#[test]
+fn test_relation_macro_dummy() {
+    let relation = TestRelation::dummy();
+
+    assert_eq!(relation.0, LookupElements::dummy());
+}